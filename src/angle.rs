@@ -0,0 +1,133 @@
+use std::f64::consts::TAU;
+
+/// Cosines of whole twelfths of a turn, where they are rational
+const COSINES: [Option<f64>; 12] = [
+    Some(1.0),
+    None,
+    Some(0.5),
+    Some(0.0),
+    Some(-0.5),
+    None,
+    Some(-1.0),
+    None,
+    Some(-0.5),
+    Some(0.0),
+    Some(0.5),
+    None,
+];
+
+/// Sines of whole twelfths of a turn, where they are rational
+const SINES: [Option<f64>; 12] = [
+    Some(0.0),
+    Some(0.5),
+    None,
+    Some(1.0),
+    None,
+    Some(0.5),
+    Some(0.0),
+    Some(-0.5),
+    None,
+    Some(-1.0),
+    None,
+    Some(-0.5),
+];
+
+/// Tangents of whole eighths of a turn
+const TANGENTS: [Option<f64>; 8] = [
+    Some(0.0),
+    Some(1.0),
+    Some(f64::INFINITY),
+    Some(-1.0),
+    Some(0.0),
+    Some(1.0),
+    Some(f64::INFINITY),
+    Some(-1.0),
+];
+
+/// The unit of angles taken by trigonometric functions and returned by their
+/// inverses
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Angle {
+    /// Degrees, with 360 to a full turn
+    Degrees,
+    /// Gradians, with 400 to a full turn
+    Gradians,
+    /// Radians, with 2π to a full turn
+    #[default]
+    Radians,
+}
+
+impl Angle {
+    /// Returns the cosine of an angle in the unit
+    pub fn cos(self, n: f64) -> f64 {
+        self.exact(n, &COSINES).unwrap_or_else(|| self.reduce(n).cos())
+    }
+
+    /// Returns the exact result of a function at whole fractions of a turn,
+    /// given as a table with one entry per fraction. Angles within rounding
+    /// error of one count, so that sin(pi) is 0 as well as sin(180°).
+    fn exact(self, n: f64, table: &[Option<f64>]) -> Option<f64> {
+        let turns = n / self.turn();
+        let steps = table.len() as f64;
+        let x = turns.rem_euclid(1.0) * steps;
+        let k = x.round();
+        let tolerance = 16.0 * f64::EPSILON * turns.abs() * steps;
+        // Beyond some magnitude, the angle is too coarse to tell
+        match (x - k).abs() <= tolerance && tolerance < 1e-6 {
+            true => table[k as usize % table.len()],
+            false => None,
+        }
+    }
+
+    /// Converts an angle in radians to the unit. Results within rounding
+    /// error of a whole number are rounded, e.g. asin(0.5) is 30 degrees
+    /// rather than 30.000000000000004.
+    pub fn from_radians(self, n: f64) -> f64 {
+        match self {
+            Angle::Radians => n,
+            _ => {
+                let x = n / TAU * self.turn();
+                match x.round() {
+                    k if (x - k).abs() <= 16.0 * f64::EPSILON * x.abs() => k,
+                    _ => x,
+                }
+            }
+        }
+    }
+
+    /// Reduces an angle in the unit to radians, within a full turn unless
+    /// already in radians
+    fn reduce(self, n: f64) -> f64 {
+        match self {
+            Angle::Radians => n,
+            _ => n.rem_euclid(self.turn()) / self.turn() * TAU,
+        }
+    }
+
+    /// Returns the sine of an angle in the unit
+    pub fn sin(self, n: f64) -> f64 {
+        self.exact(n, &SINES).unwrap_or_else(|| self.reduce(n).sin())
+    }
+
+    /// Returns the tangent of an angle in the unit
+    pub fn tan(self, n: f64) -> f64 {
+        self.exact(n, &TANGENTS).unwrap_or_else(|| self.reduce(n).tan())
+    }
+
+    /// Converts an angle in the unit to radians
+    pub fn to_radians(self, n: f64) -> f64 {
+        match self {
+            Angle::Radians => n,
+            _ => n / self.turn() * TAU,
+        }
+    }
+
+    /// Returns the size of a full turn in the unit
+    pub fn turn(self) -> f64 {
+        match self {
+            Angle::Degrees => 360.0,
+            Angle::Gradians => 400.0,
+            Angle::Radians => TAU,
+        }
+    }
+}
@@ -1,17 +1,31 @@
 extern crate clap;
 extern crate rustyline;
 
-use clap::{app_from_crate, crate_authors, crate_description, crate_name, crate_version, Arg};
+use clap::{
+    app_from_crate, crate_authors, crate_description, crate_name, crate_version, Arg, ArgGroup,
+};
 use rustyline::error::ReadlineError;
 use rustyline::Editor;
 
+use crate::angle::Angle;
+use crate::decimal::Rounding;
+use crate::environment::Environment;
 use crate::error::Error;
+use crate::format::{Formatter, Notation};
+use crate::lexer::Span;
 use crate::parser::Parser;
+use crate::value::Mode;
+
+/// The REPL input prompt
+const PROMPT: &str = "> ";
 
 /// The main CLI application
 pub struct CLI {
     debug: bool,
+    env: Environment,
+    formatter: Formatter,
     prompt: Editor<()>,
+    strict: bool,
 }
 
 impl Default for CLI {
@@ -23,25 +37,76 @@ impl Default for CLI {
 impl CLI {
     /// Creates a new CLI application
     pub fn new() -> Self {
-        Self { debug: false, prompt: Editor::<()>::new() }
+        Self {
+            debug: false,
+            env: Environment::new(),
+            formatter: Formatter::default(),
+            prompt: Editor::<()>::new(),
+            strict: false,
+        }
     }
 
-    /// Parses and evaluates the input expression, returning the numerical result
-    fn evaluate(&mut self, input: &str) -> Result<Option<f64>, Error> {
-        if !input.is_empty() {
-            let expr = Parser::new(input).parse()?;
+    /// Executes a REPL command, given without the leading :
+    fn command(&mut self, command: &str) -> Result<(), Error> {
+        let invalid = || Error::InvalidCommand(format!(":{}", command));
+        match command.split_whitespace().collect::<Vec<_>>().as_slice() {
+            ["base", base] => self.formatter.base = Self::parse_base(base).ok_or_else(invalid)?,
+            ["deg"] => self.env.set_angle(Angle::Degrees),
+            ["engineering"] => self.formatter.notation = Notation::Engineering,
+            ["fixed", decimals] => {
+                let decimals = decimals.parse().map_err(|_| invalid())?;
+                self.formatter.notation = Notation::Fixed(decimals);
+            }
+            ["grad"] => self.env.set_angle(Angle::Gradians),
+            ["general"] => self.formatter.notation = Notation::General,
+            ["mixed", "on"] => self.formatter.mixed = true,
+            ["mixed", "off"] => self.formatter.mixed = false,
+            ["rad"] => self.env.set_angle(Angle::Radians),
+            ["scientific"] => self.formatter.notation = Notation::Scientific,
+            ["separators", "on"] => self.formatter.separators = true,
+            ["separators", "off"] => self.formatter.separators = false,
+            ["significant", digits] => {
+                let digits = Self::parse_digits(digits).ok_or_else(invalid)?;
+                self.formatter.notation = Notation::Significant(digits);
+            }
+            _ => return Err(invalid()),
+        }
+        Ok(())
+    }
+
+    /// Parses and executes the input statement or REPL command, returning the
+    /// formatted result if any
+    fn evaluate(&mut self, input: &str) -> Result<Option<String>, Error> {
+        if let Some(command) = input.strip_prefix(':') {
+            self.command(command)?;
+            Ok(None)
+        } else if !input.is_empty() {
+            let statement = Parser::with_environment(input, &self.env).parse_statement()?;
             if self.debug {
-                println!("{:#?}", expr);
+                println!("{:#?}", statement);
             }
-            Ok(Some(expr.evaluate()))
+            let base = statement.base().unwrap_or(self.formatter.base);
+            let formatter = Formatter { base, factors: statement.factors(), ..self.formatter };
+            let value = statement.execute_with(&mut self.env, self.strict)?;
+            Ok(value.map(|value| formatter.format(&value)))
         } else {
             Ok(None)
         }
     }
 
+    /// Parses an output base from 2 to 36
+    fn parse_base(base: &str) -> Option<u32> {
+        base.parse().ok().filter(|base| (2..=36).contains(base))
+    }
+
+    /// Parses a positive number of digits
+    fn parse_digits(digits: &str) -> Option<usize> {
+        digits.parse().ok().filter(|digits| *digits > 0)
+    }
+
     /// Prompts the user for an input expression and returns it
     fn prompt(&mut self) -> Result<Option<String>, Error> {
-        match self.prompt.readline("> ") {
+        match self.prompt.readline(PROMPT) {
             Ok(input) => {
                 self.prompt.add_history_entry(input.as_ref());
                 Ok(Some(input))
@@ -55,25 +120,208 @@ impl CLI {
     /// Runs the CLI application
     pub fn run(&mut self) -> Result<(), Error> {
         let opts = app_from_crate!()
+            .arg(
+                Arg::with_name("angle")
+                    .short("a")
+                    .long("angle")
+                    .value_name("UNIT")
+                    .possible_values(&["deg", "grad", "rad"])
+                    .help("Sets the angle unit of trigonometric functions [default: rad]"),
+            )
+            .arg(
+                Arg::with_name("base")
+                    .short("b")
+                    .long("base")
+                    .value_name("BASE")
+                    .validator(|v| match Self::parse_base(&v) {
+                        Some(_) => Ok(()),
+                        None => Err("must be an integer from 2 to 36".into()),
+                    })
+                    .help("Displays results in the given base [default: 10]"),
+            )
+            .arg(
+                Arg::with_name("checked")
+                    .short("c")
+                    .long("checked")
+                    .requires("integer")
+                    .help("Reports fixed-width integer overflow instead of wrapping around"),
+            )
             .arg(Arg::with_name("debug").short("d").long("debug").help("Enables debug output"))
+            .arg(
+                Arg::with_name("engineering")
+                    .long("engineering")
+                    .help("Displays results in engineering notation with SI prefixes, e.g. 12.3k"),
+            )
+            .arg(
+                Arg::with_name("exact")
+                    .short("e")
+                    .long("exact")
+                    .help("Uses exact, arbitrary-precision rational arithmetic"),
+            )
+            .arg(
+                Arg::with_name("fixed")
+                    .long("fixed")
+                    .value_name("DECIMALS")
+                    .validator(|v| match v.parse::<usize>() {
+                        Ok(_) => Ok(()),
+                        _ => Err("must be a non-negative integer".into()),
+                    })
+                    .help("Displays results with the given number of decimals"),
+            )
+            .arg(
+                Arg::with_name("integer")
+                    .short("i")
+                    .long("integer")
+                    .value_name("TYPE")
+                    .conflicts_with_all(&["exact", "precision"])
+                    .possible_values(&["i8", "i16", "i32", "i64", "u8", "u16", "u32", "u64"])
+                    .help("Uses fixed-width integer arithmetic with the given integer type"),
+            )
+            .arg(
+                Arg::with_name("mixed")
+                    .short("m")
+                    .long("mixed")
+                    .help("Displays exact fractions as mixed numbers"),
+            )
+            .arg(
+                Arg::with_name("precision")
+                    .short("p")
+                    .long("precision")
+                    .value_name("DIGITS")
+                    .conflicts_with("exact")
+                    .validator(|v| match v.parse::<usize>() {
+                        Ok(p) if p > 0 => Ok(()),
+                        _ => Err("must be a positive integer".into()),
+                    })
+                    .help("Uses decimal arithmetic with the given number of significant digits"),
+            )
+            .arg(
+                Arg::with_name("rounding")
+                    .short("r")
+                    .long("rounding")
+                    .value_name("MODE")
+                    .requires("precision")
+                    .possible_values(&[
+                        "ceiling",
+                        "down",
+                        "floor",
+                        "half-down",
+                        "half-even",
+                        "half-up",
+                        "up",
+                    ])
+                    .help("Sets the decimal rounding mode [default: half-up]"),
+            )
+            .arg(
+                Arg::with_name("scientific")
+                    .long("scientific")
+                    .help("Displays results in scientific notation, e.g. 1.23e4"),
+            )
+            .arg(
+                Arg::with_name("separators")
+                    .long("separators")
+                    .help("Groups the digits of results by thousands, e.g. 1,234,567"),
+            )
+            .arg(
+                Arg::with_name("significant")
+                    .long("significant")
+                    .value_name("DIGITS")
+                    .validator(|v| match Self::parse_digits(&v) {
+                        Some(_) => Ok(()),
+                        None => Err("must be a positive integer".into()),
+                    })
+                    .help("Displays results rounded to the given number of significant digits"),
+            )
+            .group(ArgGroup::with_name("notation").args(&[
+                "engineering",
+                "fixed",
+                "scientific",
+                "significant",
+            ]))
+            .arg(
+                Arg::with_name("strict")
+                    .short("s")
+                    .long("strict")
+                    .help("Reports errors instead of returning NaN or infinity"),
+            )
             .arg(Arg::with_name("expr").index(1))
             .get_matches();
         self.debug = opts.is_present("debug");
+        self.formatter.base = opts.value_of("base").and_then(Self::parse_base).unwrap_or(10);
+        self.formatter.mixed = opts.is_present("mixed");
+        self.formatter.separators = opts.is_present("separators");
+        if opts.is_present("engineering") {
+            self.formatter.notation = Notation::Engineering;
+        } else if let Some(decimals) = opts.value_of("fixed") {
+            self.formatter.notation = Notation::Fixed(decimals.parse().unwrap_or(0));
+        } else if opts.is_present("scientific") {
+            self.formatter.notation = Notation::Scientific;
+        } else if let Some(digits) = opts.value_of("significant") {
+            self.formatter.notation = Notation::Significant(digits.parse().unwrap_or(1));
+        }
+        self.strict = opts.is_present("strict");
+        match opts.value_of("angle") {
+            Some("deg") => self.env.set_angle(Angle::Degrees),
+            Some("grad") => self.env.set_angle(Angle::Gradians),
+            _ => {}
+        }
+        if opts.is_present("exact") {
+            self.env.set_mode(Mode::Exact);
+        }
+        if let Some(precision) = opts.value_of("precision") {
+            let rounding = match opts.value_of("rounding") {
+                Some("ceiling") => Rounding::Ceiling,
+                Some("down") => Rounding::Down,
+                Some("floor") => Rounding::Floor,
+                Some("half-down") => Rounding::HalfDown,
+                Some("half-even") => Rounding::HalfEven,
+                Some("up") => Rounding::Up,
+                _ => Rounding::HalfUp,
+            };
+            let precision = precision.parse().unwrap_or(1);
+            self.env.set_mode(Mode::Decimal { precision, rounding });
+        }
+        if let Some(integer) = opts.value_of("integer") {
+            let (signed, bits) = integer.split_at(1);
+            self.env.set_mode(Mode::Fixed {
+                bits: bits.parse().unwrap_or(64),
+                signed: signed == "i",
+                checked: opts.is_present("checked"),
+            });
+        }
 
         if let Some(input) = opts.value_of("expr") {
-            if let Some(result) = self.evaluate(&input)? {
-                println!("{}", result)
-            };
+            match self.evaluate(&input) {
+                Ok(Some(result)) => println!("{}", result),
+                Ok(None) => {}
+                Err(err) => {
+                    if let Some(span) = err.span() {
+                        eprintln!("{}\n{}", input, Self::underline(span));
+                    }
+                    return Err(err);
+                }
+            }
             return Ok(());
         }
 
         while let Some(input) = self.prompt()? {
             match self.evaluate(&input) {
                 Ok(Some(result)) => println!("{}", result),
-                Err(err) => println!("Error: {}", err),
+                Err(err) => {
+                    if let Some(span) = err.span() {
+                        println!("{}{}", " ".repeat(PROMPT.len()), Self::underline(span));
+                    }
+                    println!("Error: {}", err)
+                }
                 Ok(None) => {}
             }
         }
         Ok(())
     }
+
+    /// Returns an underline marking the given span of an input line, e.g. ^~~
+    fn underline(span: Span) -> String {
+        let width = span.end.saturating_sub(span.start).max(1);
+        format!("{}^{}", " ".repeat(span.start), "~".repeat(width - 1))
+    }
 }
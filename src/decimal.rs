@@ -0,0 +1,196 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::Neg;
+
+use num::bigint::BigInt;
+use num::rational::BigRational;
+use num::{Integer, One, Signed, Zero};
+
+/// A rounding mode, used when a decimal result has more significant digits
+/// than the precision allows
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Rounding {
+    /// Rounds towards positive infinity
+    Ceiling,
+    /// Rounds towards zero, i.e. truncates
+    Down,
+    /// Rounds towards negative infinity
+    Floor,
+    /// Rounds to the nearest neighbor, with half-way cases towards zero
+    HalfDown,
+    /// Rounds to the nearest neighbor, with half-way cases to the even
+    /// neighbor, i.e. banker's rounding
+    HalfEven,
+    /// Rounds to the nearest neighbor, with half-way cases away from zero
+    #[default]
+    HalfUp,
+    /// Rounds away from zero
+    Up,
+}
+
+impl Rounding {
+    /// Rounds a rational number to an integer
+    pub fn round(self, n: &BigRational) -> BigInt {
+        let floor = n.floor().to_integer();
+        let fract = n - BigRational::from_integer(floor.clone());
+        if fract.is_zero() {
+            return floor;
+        }
+        // Compares the fraction against one half
+        let half = (fract * BigInt::from(2)).cmp(&BigRational::one());
+        let negative = n.is_negative();
+        let ceiling = match self {
+            Rounding::Ceiling => true,
+            Rounding::Down => negative,
+            Rounding::Floor => false,
+            Rounding::HalfDown => {
+                half == Ordering::Greater || (half == Ordering::Equal && negative)
+            }
+            Rounding::HalfEven => {
+                half == Ordering::Greater || (half == Ordering::Equal && floor.is_odd())
+            }
+            Rounding::HalfUp => half == Ordering::Greater || (half == Ordering::Equal && !negative),
+            Rounding::Up => !negative,
+        };
+        match ceiling {
+            true => floor + 1,
+            false => floor,
+        }
+    }
+}
+
+/// An arbitrary-precision decimal number, with the value mantissa * 10^exponent.
+/// The mantissa never has trailing zeros.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Decimal {
+    mantissa: BigInt,
+    exponent: i64,
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sign = if self.mantissa.is_negative() { "-" } else { "" };
+        let digits = self.mantissa.abs().to_string();
+        match self.exponent {
+            e if e >= 0 => write!(f, "{}{}{}", sign, digits, "0".repeat(e as usize)),
+            e if digits.len() as i64 > -e => {
+                let (int, fract) = digits.split_at((digits.len() as i64 + e) as usize);
+                write!(f, "{}{}.{}", sign, int, fract)
+            }
+            e => write!(f, "{}0.{}{}", sign, "0".repeat((-e) as usize - digits.len()), digits),
+        }
+    }
+}
+
+impl Neg for &Decimal {
+    type Output = Decimal;
+
+    fn neg(self) -> Decimal {
+        Decimal { mantissa: -&self.mantissa, exponent: self.exponent }
+    }
+}
+
+impl Decimal {
+    /// Converts a rational number to a decimal with the given number of
+    /// significant digits, rounding as necessary
+    pub fn from_ratio(n: &BigRational, precision: usize, rounding: Rounding) -> Self {
+        if n.is_zero() {
+            return Self::new(BigInt::zero(), 0);
+        }
+        let precision = precision.max(1);
+        // Scale the quotient to more than the requested number of digits, and
+        // keep track of any remainder for rounding
+        let (numer, denom) = (n.numer().abs(), n.denom());
+        let scale = precision as i64 + 1 - (Self::digits(&numer) - Self::digits(denom));
+        let (quotient, remainder) = match scale {
+            s if s >= 0 => (numer * Self::pow10(s as usize)).div_rem(denom),
+            s => numer.div_rem(&(denom * Self::pow10(-s as usize))),
+        };
+        let sign = if n.is_negative() { -BigInt::one() } else { BigInt::one() };
+        Self::round(sign * quotient, -scale, !remainder.is_zero(), precision, rounding)
+    }
+
+    /// Returns true if the decimal is a whole number
+    pub fn is_integer(&self) -> bool {
+        self.exponent >= 0
+    }
+
+    /// Returns true if the decimal is less than zero
+    pub fn is_negative(&self) -> bool {
+        self.mantissa.is_negative()
+    }
+
+    /// Returns true if the decimal is zero
+    pub fn is_zero(&self) -> bool {
+        self.mantissa.is_zero()
+    }
+
+    /// Converts the decimal to an exact rational number
+    pub fn to_ratio(&self) -> BigRational {
+        match self.exponent {
+            e if e >= 0 => BigRational::from_integer(&self.mantissa * Self::pow10(e as usize)),
+            e => BigRational::new(self.mantissa.clone(), Self::pow10(-e as usize)),
+        }
+    }
+
+    /// Creates a new decimal, normalizing the mantissa
+    fn new(mut mantissa: BigInt, mut exponent: i64) -> Self {
+        let ten = BigInt::from(10);
+        if mantissa.is_zero() {
+            exponent = 0;
+        }
+        while !mantissa.is_zero() && mantissa.is_multiple_of(&ten) {
+            mantissa /= &ten;
+            exponent += 1;
+        }
+        Self { mantissa, exponent }
+    }
+
+    /// Returns the number of decimal digits in an integer
+    fn digits(n: &BigInt) -> i64 {
+        n.abs().to_string().len() as i64
+    }
+
+    /// Returns 10 raised to the given power
+    fn pow10(exp: usize) -> BigInt {
+        num::pow(BigInt::from(10), exp)
+    }
+
+    /// Rounds a mantissa with more than the given number of significant digits.
+    /// The sticky flag signifies nonzero digits beyond the mantissa.
+    fn round(
+        mantissa: BigInt,
+        exponent: i64,
+        sticky: bool,
+        precision: usize,
+        rounding: Rounding,
+    ) -> Self {
+        let excess = (Self::digits(&mantissa) - precision as i64).max(1) as usize;
+        let (mut quotient, remainder) = mantissa.abs().div_rem(&Self::pow10(excess));
+        // Compares the discarded digits against half a unit in the last place
+        let half = match (&remainder * 2u32).cmp(&Self::pow10(excess)) {
+            Ordering::Equal if sticky => Ordering::Greater,
+            ord => ord,
+        };
+        let negative = mantissa.is_negative();
+        let increment = (sticky || !remainder.is_zero())
+            && match rounding {
+                Rounding::Ceiling => !negative,
+                Rounding::Down => false,
+                Rounding::Floor => negative,
+                Rounding::HalfDown => half == Ordering::Greater,
+                Rounding::HalfEven => {
+                    half == Ordering::Greater || (half == Ordering::Equal && quotient.is_odd())
+                }
+                Rounding::HalfUp => half != Ordering::Less,
+                Rounding::Up => true,
+            };
+        if increment {
+            quotient += 1;
+        }
+        if negative {
+            quotient = -quotient;
+        }
+        Self::new(quotient, exponent + excess as i64)
+    }
+}
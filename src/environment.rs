@@ -0,0 +1,94 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::angle::Angle;
+use crate::function::{Arity, Function};
+use crate::value::{Mode, Value};
+
+/// An evaluation environment, holding user-bound variables and functions as
+/// well as constants and native functions registered by an embedding
+/// application. Built-in constants and functions take precedence over these.
+#[derive(Clone, Debug, Default)]
+pub struct Environment {
+    angle: Angle,
+    constants: HashMap<String, f64>,
+    functions: HashMap<String, Rc<Function>>,
+    mode: Mode,
+    variables: HashMap<String, Value>,
+}
+
+impl Environment {
+    /// Creates a new, empty environment
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the unit of angles used by trigonometric functions
+    pub fn angle(&self) -> Angle {
+        self.angle
+    }
+
+    /// Returns the value of a registered constant, if any
+    pub fn constant(&self, name: &str) -> Option<f64> {
+        self.constants.get(name).cloned()
+    }
+
+    /// Defines a function, replacing any existing function with the same name
+    pub fn define(&mut self, function: Function) {
+        self.functions.insert(function.name().to_string(), Rc::new(function));
+    }
+
+    /// Returns a function, if defined
+    pub fn function(&self, name: &str) -> Option<Rc<Function>> {
+        self.functions.get(name).cloned()
+    }
+
+    /// Returns the value of a variable, if bound
+    pub fn get(&self, name: &str) -> Option<f64> {
+        self.variables.get(name).map(Value::to_f64)
+    }
+
+    /// Returns the numeric mode used during evaluation
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// Registers a named constant, which can't be reassigned by statements
+    pub fn register_constant(&mut self, name: &str, value: f64) {
+        self.constants.insert(name.to_string(), value);
+    }
+
+    /// Registers a native function, which is called with the evaluated
+    /// arguments once the argument count has been checked against the arity
+    pub fn register_function<A, F>(&mut self, name: &str, arity: A, func: F)
+    where
+        A: Into<Arity>,
+        F: Fn(&[f64]) -> f64 + 'static,
+    {
+        self.define(Function::Native {
+            name: name.to_string(),
+            arity: arity.into(),
+            func: Rc::new(func),
+        })
+    }
+
+    /// Binds a variable to a value, replacing any existing value
+    pub fn set<V: Into<Value>>(&mut self, name: &str, value: V) {
+        self.variables.insert(name.to_string(), value.into());
+    }
+
+    /// Sets the unit of angles used by trigonometric functions
+    pub fn set_angle(&mut self, angle: Angle) {
+        self.angle = angle;
+    }
+
+    /// Sets the numeric mode used during evaluation
+    pub fn set_mode(&mut self, mode: Mode) {
+        self.mode = mode;
+    }
+
+    /// Returns the exact value of a variable, if bound
+    pub fn value(&self, name: &str) -> Option<&Value> {
+        self.variables.get(name)
+    }
+}
@@ -1,13 +1,198 @@
 use rustyline::error::ReadlineError;
 
+use std::error;
 use std::fmt;
 use std::io;
-use std::num;
 
+use crate::function::Arity;
+use crate::lexer::{Span, Token};
+
+/// What the parser expected to find in place of an unexpected token
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expected {
+    /// The end of the input
+    End,
+    /// A specific token
+    Token(Token),
+    /// A unit, e.g. after a conversion
+    Unit,
+    /// A value, i.e. a number, constant, function call, or parenthesized expression
+    Value,
+}
+
+/// An evaluation error, returned by strict evaluation in place of NaN or
+/// infinite results, and by any evaluation for invalid use of units or lists
+#[derive(Clone, Debug, PartialEq)]
+pub enum EvalError {
+    /// Division or modulo by zero
+    DivisionByZero,
+
+    /// An argument outside of a function's domain, e.g. the square root of a
+    /// negative number
+    Domain { function: String, value: f64 },
+
+    /// A value which is not a list, where a list was expected, e.g. len(3)
+    ExpectedList,
+
+    /// A value which is not a matrix, i.e. a list of rows of the same length,
+    /// where a matrix was expected, e.g. det([1, 2])
+    ExpectedMatrix,
+
+    /// An addition, subtraction, or conversion between quantities of different
+    /// dimensions, e.g. 3 m + 2 s. Dimensionless values have the unit 1.
+    IncompatibleUnits { lhs: String, rhs: String },
+
+    /// An index which is not an integer within the bounds of the list, e.g.
+    /// [1, 2][2]
+    InvalidIndex { index: f64, length: usize },
+
+    /// An element-wise operation on lists of different lengths, e.g.
+    /// [1, 2] + [1, 2, 3]
+    LengthMismatch { lhs: usize, rhs: usize },
+
+    /// A quantity raised to a power which doesn't give integer powers of its
+    /// units, e.g. (4 m)^0.5
+    NonIntegerPower { quantity: String },
+
+    /// A complex result, where a real number was expected
+    NotReal,
+
+    /// A matrix which is not square, where a square matrix was expected
+    NotSquare { rows: usize, columns: usize },
+
+    /// A matrix which is not symmetric, where a symmetric matrix was expected
+    NotSymmetric,
+
+    /// A result too large to be represented, for finite operands
+    Overflow,
+
+    /// A matrix without an inverse, e.g. inv([[1, 2], [2, 4]])
+    Singular,
+
+    /// A result which is not a number, e.g. infinity minus infinity
+    Undefined,
+
+    /// A list where a single value was expected, e.g. the condition of if()
+    UnexpectedList,
+
+    /// A quantity where a dimensionless value was expected, e.g. sin(3 m)
+    UnexpectedUnit { unit: String },
+
+    /// A reference to a variable which is not bound in the environment
+    UnknownVariable { name: String },
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EvalError::DivisionByZero => write!(f, "Division by zero"),
+            EvalError::Domain { function, value } => {
+                write!(f, "Invalid argument {} for {}", value, function)
+            }
+            EvalError::ExpectedList => write!(f, "Expected a list"),
+            EvalError::ExpectedMatrix => write!(f, "Expected a matrix"),
+            EvalError::IncompatibleUnits { lhs, rhs } => {
+                write!(f, "Incompatible units {} and {}", lhs, rhs)
+            }
+            EvalError::InvalidIndex { index, length } => {
+                write!(f, "Invalid index {} for list of length {}", index, length)
+            }
+            EvalError::LengthMismatch { lhs, rhs } => {
+                write!(f, "Mismatched list lengths {} and {}", lhs, rhs)
+            }
+            EvalError::NonIntegerPower { quantity } => {
+                write!(f, "Non-integer power of quantity {}", quantity)
+            }
+            EvalError::NotReal => write!(f, "Result is not a real number"),
+            EvalError::NotSquare { rows, columns } => {
+                write!(f, "Expected a square matrix, not {}x{}", rows, columns)
+            }
+            EvalError::NotSymmetric => write!(f, "Expected a symmetric matrix"),
+            EvalError::Overflow => write!(f, "Numeric overflow"),
+            EvalError::Singular => write!(f, "Singular matrix"),
+            EvalError::Undefined => write!(f, "Undefined result"),
+            EvalError::UnexpectedList => write!(f, "Unexpected list"),
+            EvalError::UnexpectedUnit { unit } => write!(f, "Unexpected unit {}", unit),
+            EvalError::UnknownVariable { name } => write!(f, "Unknown variable {}", name),
+        }
+    }
+}
+
+impl error::Error for EvalError {}
+
+/// A rustcalc error. Parse errors carry the span of input they refer to.
 #[derive(Clone, PartialEq)]
 pub enum Error {
+    /// A function was called with the wrong number of arguments
+    ArityMismatch { name: String, expected: Arity, got: usize, span: Span },
+
+    /// An assignment to a built-in or registered constant
+    AssignConstant { name: String, span: Span },
+
+    /// A function definition with the same parameter name given twice
+    DuplicateParameter { name: String, span: Span },
+
+    /// An error during strict evaluation
+    Evaluate(EvalError),
+
+    /// A base which is not an integer from 2 to 36
+    InvalidBase { base: String, span: Span },
+
+    /// A REPL command which is unknown or has invalid arguments
+    InvalidCommand(String),
+
+    /// A digit which is not valid for the radix of a number literal, e.g. 0b12
+    InvalidDigit { digit: char, radix: u32, span: Span },
+
+    /// A number literal which could not be parsed
+    InvalidNumber { literal: String, span: Span },
+
+    /// A function definition with a parameter name that is not allowed
+    InvalidParameter { name: String, span: Span },
+
+    /// An input/output error
     IO(String),
-    Parse(String),
+
+    /// A definition of a function which can't be redefined, i.e. a built-in
+    /// or native function
+    RedefineFunction { name: String, span: Span },
+
+    /// A character which is not valid in any token
+    UnexpectedCharacter { found: char, span: Span },
+
+    /// The input ended while more tokens were expected
+    UnexpectedEnd { span: Span },
+
+    /// A token which is not valid at its position
+    UnexpectedToken { found: Token, expected: Expected, span: Span },
+
+    /// A reference to an unknown constant or variable
+    UnknownConstant { name: String, span: Span },
+
+    /// A call to an unknown function
+    UnknownFunction { name: String, span: Span },
+}
+
+impl Error {
+    /// Returns the span of input the error refers to, if any
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            Error::ArityMismatch { span, .. }
+            | Error::AssignConstant { span, .. }
+            | Error::DuplicateParameter { span, .. }
+            | Error::InvalidBase { span, .. }
+            | Error::InvalidDigit { span, .. }
+            | Error::InvalidNumber { span, .. }
+            | Error::InvalidParameter { span, .. }
+            | Error::RedefineFunction { span, .. }
+            | Error::UnexpectedCharacter { span, .. }
+            | Error::UnexpectedEnd { span }
+            | Error::UnexpectedToken { span, .. }
+            | Error::UnknownConstant { span, .. }
+            | Error::UnknownFunction { span, .. } => Some(*span),
+            Error::Evaluate(_) | Error::InvalidCommand(_) | Error::IO(_) => None,
+        }
+    }
 }
 
 impl fmt::Debug for Error {
@@ -19,20 +204,49 @@ impl fmt::Debug for Error {
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Error::IO(s) | Error::Parse(s) => write!(f, "{}", s),
+            Error::ArityMismatch { name, expected, got, .. } if *got < expected.min => {
+                write!(f, "Missing argument for {}()", name)
+            }
+            Error::ArityMismatch { name, .. } => write!(f, "Unexpected argument for {}()", name),
+            Error::AssignConstant { name, .. } => write!(f, "Cannot assign to constant {}", name),
+            Error::DuplicateParameter { name, .. } => write!(f, "Duplicate parameter {}", name),
+            Error::Evaluate(err) => write!(f, "{}", err),
+            Error::InvalidBase { base, .. } => write!(f, "Invalid base {}, expected 2 to 36", base),
+            Error::InvalidCommand(command) => write!(f, "Invalid command {}", command),
+            Error::InvalidDigit { digit, radix, .. } => {
+                write!(f, "Invalid digit {} for base {}", digit, radix)
+            }
+            Error::InvalidNumber { literal, .. } => write!(f, "Invalid number {}", literal),
+            Error::InvalidParameter { name, .. } => write!(f, "Invalid parameter {}", name),
+            Error::IO(s) => write!(f, "{}", s),
+            Error::RedefineFunction { name, .. } => {
+                write!(f, "Cannot redefine function {}", name)
+            }
+            Error::UnexpectedCharacter { found, .. } => write!(f, "Unexpected character {}", found),
+            Error::UnexpectedEnd { .. } => write!(f, "Unexpected end of input"),
+            Error::UnexpectedToken { found, expected, .. } => match expected {
+                Expected::End => write!(f, "Unexpected token {}", found),
+                Expected::Token(t) => write!(f, "Expected token {}, found {}", t, found),
+                Expected::Unit => write!(f, "Expected unit, found {}", found),
+                Expected::Value => write!(f, "Expected value, found {}", found),
+            },
+            Error::UnknownConstant { name, .. } => write!(f, "Unknown constant {}", name),
+            Error::UnknownFunction { name, .. } => write!(f, "Unknown function {}", name),
         }
     }
 }
 
-impl From<io::Error> for Error {
-    fn from(err: io::Error) -> Self {
-        Error::IO(err.to_string())
+impl error::Error for Error {}
+
+impl From<EvalError> for Error {
+    fn from(err: EvalError) -> Self {
+        Error::Evaluate(err)
     }
 }
 
-impl From<num::ParseFloatError> for Error {
-    fn from(err: num::ParseFloatError) -> Self {
-        Error::Parse(err.to_string())
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::IO(err.to_string())
     }
 }
 
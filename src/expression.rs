@@ -1,10 +1,32 @@
+use std::cmp::Ordering;
 use std::f64;
+use std::rc::Rc;
+
+use num::bigint::BigInt;
+use num::complex::Complex64;
+use num::{Integer, Signed, ToPrimitive};
+
+use crate::angle::Angle;
+use crate::decimal::Rounding;
+use crate::environment::Environment;
+use crate::error::EvalError;
+use crate::function::Function;
+use crate::gamma;
+use crate::integer;
+use crate::matrix::{self, Matrix, MAX_ELEMENTS};
+use crate::statistics::Aggregate;
+use crate::unit::Unit;
+use crate::value::{Mode, Value};
 
 /// Mathematical constants
 #[derive(Clone, Debug)]
 pub enum Constant {
+    /// A named constant registered in the environment, with its value
+    Custom(String, f64),
     /// The base of the natural logarithm
     E,
+    /// The imaginary unit
+    I,
     /// The IEEE 754 special value infinity
     Infinity,
     /// The IEEE 754 special value not-a-number (NaN)
@@ -13,72 +35,353 @@ pub enum Constant {
     Pi,
 }
 
-impl From<&Constant> for f64 {
+impl From<&Constant> for Value {
     fn from(c: &Constant) -> Self {
         match c {
-            Constant::E => f64::consts::E,
-            Constant::Infinity => f64::INFINITY,
-            Constant::NaN => f64::NAN,
-            Constant::Pi => f64::consts::PI,
+            Constant::Custom(_, value) => Value::Float(*value),
+            Constant::E => Value::Float(f64::consts::E),
+            Constant::I => Value::Complex(Complex64::i()),
+            Constant::Infinity => Value::Float(f64::INFINITY),
+            Constant::NaN => Value::Float(f64::NAN),
+            Constant::Pi => Value::Float(f64::consts::PI),
         }
     }
 }
 
-/// A mathematical operation or entity that evaluates to a f64
+/// A mathematical operation or entity that evaluates to a value
 #[derive(Clone, Debug)]
 pub enum Expression {
+    /// Returns the absolute value of the argument, i.e. the magnitude of
+    /// complex numbers
+    Absolute(Box<Expression>),
+
     /// Adds two terms
     Add { lhs: Box<Expression>, rhs: Box<Expression> },
 
+    /// Applies a function to any number of arguments, e.g. their sum
+    Aggregate { function: Aggregate, args: Vec<Expression> },
+
+    /// Returns 1 if both operands are nonzero, or 0 otherwise. The RHS is only
+    /// evaluated if the LHS is nonzero.
+    And { lhs: Box<Expression>, rhs: Box<Expression> },
+
+    /// Returns the inverse cosine of the argument, as an angle in the angle unit
+    ArcCosine(Box<Expression>),
+
+    /// Returns the inverse sine of the argument, as an angle in the angle unit
+    ArcSine(Box<Expression>),
+
+    /// Returns the inverse tangent of the argument, as an angle in the angle unit
+    ArcTangent(Box<Expression>),
+
+    /// Returns the angle of the point (x, y) from the positive x axis, in the
+    /// range -pi to pi radians or the equivalent in the angle unit
+    ArcTangent2 { y: Box<Expression>, x: Box<Expression> },
+
+    /// Returns the argument (angle) of a complex number
+    Argument(Box<Expression>),
+
+    /// Returns the base 2 logarithm of the argument
+    BinaryLogarithm(Box<Expression>),
+
+    /// Returns the binomial coefficient of two non-negative integers, i.e. the
+    /// number of ways to choose k out of n items
+    Binomial { n: Box<Expression>, k: Box<Expression> },
+
+    /// Returns the bitwise and of two integers
+    BitAnd { lhs: Box<Expression>, rhs: Box<Expression> },
+
+    /// Returns the bitwise complement of an integer
+    BitNot(Box<Expression>),
+
+    /// Returns the bitwise or of two integers
+    BitOr { lhs: Box<Expression>, rhs: Box<Expression> },
+
+    /// Returns the bitwise exclusive or of two integers
+    BitXor { lhs: Box<Expression>, rhs: Box<Expression> },
+
+    /// Calls a native or user-defined function with the given arguments
+    Call { function: Rc<Function>, args: Vec<Expression> },
+
+    /// Rounds the argument up to the nearest integer
+    Ceiling(Box<Expression>),
+
+    /// Returns the base 10 logarithm of the argument
+    CommonLogarithm(Box<Expression>),
+
+    /// Returns the complex conjugate of the argument
+    Conjugate(Box<Expression>),
+
     /// A named mathematical constant
     Constant(Constant),
 
-    /// Returns the cosine of the argument angle in radians
+    /// Converts a quantity to the given unit of the same dimension
+    Convert { value: Box<Expression>, unit: Unit },
+
+    /// Converts a value to the given base from 2 to 36 for display, and
+    /// otherwise evaluates to the value itself
+    ConvertBase { value: Box<Expression>, base: u32 },
+
+    /// Returns the cosecant of the argument angle in the angle unit, i.e.
+    /// 1 / sin
+    Cosecant(Box<Expression>),
+
+    /// Returns the cosine of the argument angle in the angle unit
     Cosine(Box<Expression>),
 
+    /// Returns the cotangent of the argument angle in the angle unit, i.e. 1 / tan
+    Cotangent(Box<Expression>),
+
+    /// Returns the cross product of two lists of three elements
+    CrossProduct { lhs: Box<Expression>, rhs: Box<Expression> },
+
+    /// Returns the real cube root of the argument, e.g. -2 for -8
+    CubeRoot(Box<Expression>),
+
+    /// Converts an angle in degrees, e.g. 30°, to the angle unit
+    DegreeAngle(Box<Expression>),
+
     /// Converts the argument from radians to degrees
     Degrees(Box<Expression>),
 
+    /// Returns the determinant of a square matrix
+    Determinant(Box<Expression>),
+
     /// Divides two values
     Divide { lhs: Box<Expression>, rhs: Box<Expression> },
 
+    /// Returns the dot product of two lists of the same length
+    DotProduct { lhs: Box<Expression>, rhs: Box<Expression> },
+
+    /// Returns the double factorial of a non-negative integer, i.e. the product
+    /// of the integers from 1 to n with the same parity
+    DoubleFactorial(Box<Expression>),
+
+    /// Returns the eigenvalues of a real symmetric matrix in ascending order
+    Eigenvalues(Box<Expression>),
+
+    /// Returns 1 if the operands are equal, or 0 otherwise
+    Equal { lhs: Box<Expression>, rhs: Box<Expression> },
+
+    /// Returns e raised to the power of the argument
+    Exponential(Box<Expression>),
+
+    /// Returns e raised to the power of the argument, minus 1, which is
+    /// accurate even for arguments close to zero
+    ExponentialMinusOne(Box<Expression>),
+
     /// Raises the LHS value to the power of the RHS
     Exponentiate { lhs: Box<Expression>, rhs: Box<Expression> },
 
     /// Returns the factorial value of the argument
     Factorial(Box<Expression>),
 
+    /// Evaluates an integer, which is displayed as its prime factorization
+    Factorize(Box<Expression>),
+
+    /// Rounds the argument down to the nearest integer
+    Floor(Box<Expression>),
+
+    /// Returns the fractional part of the argument, with the same sign
+    FractionalPart(Box<Expression>),
+
+    /// Returns the gamma function of the argument, i.e. (n - 1)! for positive
+    /// integers
+    Gamma(Box<Expression>),
+
+    /// Returns the greatest common divisor of two integers
+    GreatestCommonDivisor { lhs: Box<Expression>, rhs: Box<Expression> },
+
+    /// Returns 1 if the LHS is greater than the RHS, or 0 otherwise
+    Greater { lhs: Box<Expression>, rhs: Box<Expression> },
+
+    /// Returns 1 if the LHS is greater than or equal to the RHS, or 0 otherwise
+    GreaterEqual { lhs: Box<Expression>, rhs: Box<Expression> },
+
+    /// Returns the hyperbolic cosine of the argument
+    HyperbolicCosine(Box<Expression>),
+
+    /// Returns the hyperbolic sine of the argument
+    HyperbolicSine(Box<Expression>),
+
+    /// Returns the hyperbolic tangent of the argument
+    HyperbolicTangent(Box<Expression>),
+
+    /// Returns the hypotenuse of a right triangle with the given legs, i.e.
+    /// sqrt(|a|^2 + |b|^2)
+    Hypotenuse { lhs: Box<Expression>, rhs: Box<Expression> },
+
+    /// Returns the identity matrix with the given number of rows and columns
+    Identity(Box<Expression>),
+
+    /// Evaluates to the then branch if the condition is nonzero, or to the
+    /// otherwise branch if not. Only the chosen branch is evaluated.
+    If { condition: Box<Expression>, then: Box<Expression>, otherwise: Box<Expression> },
+
+    /// Returns the imaginary part of a complex number
+    Imaginary(Box<Expression>),
+
+    /// Returns the element of a list at the given index, counting from 0, or
+    /// from the end for negative indices
+    Index { value: Box<Expression>, index: Box<Expression> },
+
+    /// Returns the inverse hyperbolic cosine of the argument
+    InverseHyperbolicCosine(Box<Expression>),
+
+    /// Returns the inverse hyperbolic sine of the argument
+    InverseHyperbolicSine(Box<Expression>),
+
+    /// Returns the inverse hyperbolic tangent of the argument
+    InverseHyperbolicTangent(Box<Expression>),
+
+    /// Returns 1 if the argument is a prime number, or 0 otherwise
+    IsPrime(Box<Expression>),
+
+    /// Returns the least common multiple of two integers
+    LeastCommonMultiple { lhs: Box<Expression>, rhs: Box<Expression> },
+
+    /// Returns the number of elements in a list
+    Length(Box<Expression>),
+
+    /// Returns 1 if the LHS is less than the RHS, or 0 otherwise
+    Less { lhs: Box<Expression>, rhs: Box<Expression> },
+
+    /// Returns 1 if the LHS is less than or equal to the RHS, or 0 otherwise
+    LessEqual { lhs: Box<Expression>, rhs: Box<Expression> },
+
+    /// Evaluates the elements of a list, e.g. [1, 2, 3]
+    List(Vec<Expression>),
+
+    /// Returns the logarithm of the value in the given base
+    Logarithm { value: Box<Expression>, base: Box<Expression> },
+
+    /// Returns the natural logarithm of 1 plus the argument, which is accurate
+    /// even for arguments close to zero
+    LogarithmOnePlus(Box<Expression>),
+
+    /// Returns the natural logarithm of the absolute value of the gamma
+    /// function of the argument, which doesn't overflow for large arguments
+    LogGamma(Box<Expression>),
+
+    /// Returns the inverse of a square matrix
+    MatrixInverse(Box<Expression>),
+
+    /// Returns the inverse of an integer modulo a positive integer, i.e. x such
+    /// that value * x = 1 (mod modulus)
+    ModularInverse { value: Box<Expression>, modulus: Box<Expression> },
+
+    /// Raises an integer to an integer power modulo a positive integer, where
+    /// negative exponents raise the modular inverse
+    ModularPower { base: Box<Expression>, exponent: Box<Expression>, modulus: Box<Expression> },
+
     /// Returns the modulo of the arguments, with the sign of the RHS and
     /// magnitude less than the LHS
     Modulo { lhs: Box<Expression>, rhs: Box<Expression> },
 
-    /// Multiplies the LHS by the RHS
+    /// Multiplies the LHS by the RHS, as matrices if either is a matrix
     Multiply { lhs: Box<Expression>, rhs: Box<Expression> },
 
+    /// Returns the natural (base e) logarithm of the argument
+    NaturalLogarithm(Box<Expression>),
+
     /// Negates the argument
     Negate(Box<Expression>),
 
+    /// Returns the smallest prime number greater than the argument
+    NextPrime(Box<Expression>),
+
+    /// Returns the Euclidean norm of a list, i.e. the square root of the sum
+    /// of the squared magnitudes of its elements
+    Norm(Box<Expression>),
+
+    /// Returns 1 if the argument is zero, or 0 otherwise
+    Not(Box<Expression>),
+
+    /// Returns 1 if the operands are not equal, or 0 otherwise
+    NotEqual { lhs: Box<Expression>, rhs: Box<Expression> },
+
     /// Represents a numerical value
-    Number(f64),
+    Number(Value),
+
+    /// Returns 1 if either operand is nonzero, or 0 otherwise. The RHS is only
+    /// evaluated if the LHS is zero.
+    Or { lhs: Box<Expression>, rhs: Box<Expression> },
+
+    /// A parameter of the enclosing user-defined function, by position
+    Parameter(usize),
+
+    /// Returns the number of permutations of two non-negative integers, i.e.
+    /// the number of ways to arrange k out of n items
+    Permutations { n: Box<Expression>, k: Box<Expression> },
 
     /// Converts the argument from degrees to radians
     Radians(Box<Expression>),
 
-    /// Rounds a value to a given number of decimals. Returns NaN for negative or
-    /// fractional decimals.
-    Round { value: Box<Expression>, decimals: Box<Expression> },
+    /// Returns the rank of a matrix, i.e. the number of linearly independent
+    /// rows
+    Rank(Box<Expression>),
+
+    /// Returns the real part of a complex number
+    Real(Box<Expression>),
+
+    /// Returns the real root of the value with the given degree, i.e. the
+    /// negative root for negative values and odd degrees
+    Root { value: Box<Expression>, degree: Box<Expression> },
+
+    /// Rounds a value to a given number of decimals with a rounding mode.
+    /// Returns NaN for negative or fractional decimals.
+    Round { value: Box<Expression>, decimals: Box<Expression>, rounding: Rounding },
+
+    /// Rounds a value to a multiple of a step with a rounding mode, e.g. 0.25.
+    /// Returns NaN for a zero step.
+    RoundTo { value: Box<Expression>, step: Box<Expression>, rounding: Rounding },
+
+    /// Returns the secant of the argument angle in the angle unit, i.e.
+    /// 1 / cos
+    Secant(Box<Expression>),
+
+    /// Shifts the bits of the LHS integer left by the RHS number of bits
+    ShiftLeft { lhs: Box<Expression>, rhs: Box<Expression> },
 
-    /// Returns the sine of the argument angle in radians
+    /// Shifts the bits of the LHS integer right by the RHS number of bits,
+    /// keeping the sign
+    ShiftRight { lhs: Box<Expression>, rhs: Box<Expression> },
+
+    /// Returns the sign of the argument, i.e. -1, 0, or 1
+    Sign(Box<Expression>),
+
+    /// Returns the sine of the argument angle in the angle unit
     Sine(Box<Expression>),
 
-    /// Takes the square root of the argument
+    /// Solves the linear equations matrix * x = rhs for x, where the RHS is a
+    /// vector or a matrix with as many rows as the square matrix
+    Solve { matrix: Box<Expression>, rhs: Box<Expression> },
+
+    /// Takes the square root of the argument, which is complex for negative
+    /// numbers
     SquareRoot(Box<Expression>),
 
     /// Subtracts the RHS from the LHS
     Subtract { lhs: Box<Expression>, rhs: Box<Expression> },
 
-    /// Returns the tangent of the argument angle in radians
+    /// Returns the tangent of the argument angle in the angle unit
     Tangent(Box<Expression>),
+
+    /// Returns Euler's totient of a positive integer, i.e. the number of
+    /// integers from 1 to n that are coprime to it
+    Totient(Box<Expression>),
+
+    /// Returns the transpose of a matrix, or of a vector as a row
+    Transpose(Box<Expression>),
+
+    /// Rounds the argument towards zero to an integer
+    Truncate(Box<Expression>),
+
+    /// A named variable, bound in the environment
+    Variable(String),
+
+    /// Returns a matrix of zeros with the given number of rows and columns
+    Zeros { rows: Box<Expression>, columns: Box<Expression> },
 }
 
 impl From<Constant> for Expression {
@@ -89,60 +392,816 @@ impl From<Constant> for Expression {
 
 impl From<f64> for Expression {
     fn from(n: f64) -> Self {
-        Expression::Number(n)
+        Expression::Number(n.into())
     }
 }
 
 impl From<f64> for Box<Expression> {
     fn from(n: f64) -> Self {
-        Box::new(Expression::Number(n))
+        Box::new(Expression::Number(n.into()))
     }
 }
 
-impl From<&Expression> for f64 {
-    fn from(expr: &Expression) -> Self {
-        expr.evaluate()
+impl From<Value> for Expression {
+    fn from(value: Value) -> Self {
+        Expression::Number(value)
     }
 }
 
 impl Expression {
-    /// Evaluates the expression to a f64. Returns f64::NAN or f64::INFINITY on error.
-    pub fn evaluate(&self) -> f64 {
+    /// Returns the base a value should be displayed in, if the expression
+    /// converts it to one
+    pub fn base(&self) -> Option<u32> {
+        match self {
+            Expression::ConvertBase { base, .. } => Some(*base),
+            _ => None,
+        }
+    }
+
+    /// Returns true if a value should be displayed as its prime factorization,
+    /// i.e. if the expression factorizes it
+    pub fn factors(&self) -> bool {
+        matches!(self, Expression::Factorize(_))
+    }
+
+    /// Evaluates the expression to a f64, looking up variables in the given
+    /// environment. Returns f64::NAN or f64::INFINITY on error, and the
+    /// magnitude of quantities.
+    pub fn evaluate(&self, env: &Environment) -> f64 {
+        self.evaluate_value(env).to_f64()
+    }
+
+    /// Evaluates the expression to a value using the numeric mode of the given
+    /// environment, e.g. exact integers in exact mode. Returns f64::NAN or
+    /// f64::INFINITY on error.
+    pub fn evaluate_value(&self, env: &Environment) -> Value {
+        self.evaluate_with(env, &[], false).unwrap_or(Value::Float(f64::NAN))
+    }
+
+    /// Evaluates the expression to a f64 in strict mode, looking up variables
+    /// in the given environment. Returns an error for operations that would
+    /// otherwise yield NaN, or infinity for finite operands, as well as for
+    /// complex results.
+    pub fn try_evaluate(&self, env: &Environment) -> Result<f64, EvalError> {
+        match self.evaluate_with(env, &[], true)? {
+            value if value.is_complex() => Err(EvalError::NotReal),
+            value if value.is_infinite() => Ok(value.to_f64()),
+            value => match value.to_f64() {
+                n if n.is_infinite() => Err(EvalError::Overflow),
+                n => Ok(n),
+            },
+        }
+    }
+
+    /// Evaluates the expression with the given parameter values, i.e. the
+    /// local scope of the enclosing function call. Only returns errors in
+    /// strict mode, or for invalid use of units.
+    pub(crate) fn evaluate_with(
+        &self,
+        env: &Environment,
+        params: &[Value],
+        strict: bool,
+    ) -> Result<Value, EvalError> {
+        let eval = |expr: &Expression| expr.evaluate_with(env, params, strict);
+        let angle = env.angle();
+        // Checks the result of an operation in strict mode, which must not be NaN,
+        // nor infinite or complex unless any operand is, and converts it to the
+        // numeric mode. Values have themselves as operand. Overflow is checked in
+        // any mode if a checked fixed-width mode requires it.
+        let check = |value: Value, operands: &[&Value]| match value {
+            v if v.overflows(env.mode()) => Err(EvalError::Overflow),
+            v if strict && v.is_nan() => Err(EvalError::Undefined),
+            v if strict && v.is_infinite() && !operands.iter().any(|o| o.is_infinite()) => {
+                Err(EvalError::Overflow)
+            }
+            v if strict && v.is_complex() && !operands.iter().any(|o| o.is_complex()) => {
+                Err(EvalError::NotReal)
+            }
+            v => Ok(v.into_mode(env.mode())),
+        };
+        // Handles an argument outside of a function's domain
+        let domain = |function: &str, value: &Value| match strict {
+            true => {
+                Err(EvalError::Domain { function: function.to_string(), value: value.to_f64() })
+            }
+            false => Ok(Value::Float(f64::NAN)),
+        };
+        // Handles a quantity where a dimensionless value was expected
+        let unexpected = |unit: &Unit| -> Result<Value, EvalError> {
+            Err(EvalError::UnexpectedUnit { unit: unit.to_string() })
+        };
+        // Converts the RHS of an addition, subtraction, or modulo to the unit of
+        // the LHS, which must have the same dimension
+        let like = |l: &Value, r: Value| match (l.unit(), r.unit()) {
+            (None, None) => Ok(r),
+            (lhs, rhs) => {
+                let (lhs, rhs) =
+                    (lhs.cloned().unwrap_or_default(), rhs.cloned().unwrap_or_default());
+                r.convert(&lhs).ok_or_else(|| EvalError::IncompatibleUnits {
+                    lhs: lhs.to_string(),
+                    rhs: rhs.to_string(),
+                })
+            }
+        };
+        // Compares the LHS to the RHS, which must have the same dimension, giving
+        // 1 if the ordering satisfies the predicate or 0 otherwise. NaN is
+        // unordered, and complex numbers can't be ordered.
+        let compare = |lhs: &Expression, rhs: &Expression, f: fn(Ordering) -> bool| {
+            let l = eval(lhs)?;
+            let r = like(&l, eval(rhs)?)?;
+            match l.compare(&r) {
+                None if strict && (l.is_complex() || r.is_complex()) => Err(EvalError::NotReal),
+                ordering => check(ordering.is_some_and(f).into(), &[&l, &r]),
+            }
+        };
+        // Evaluates a condition, which is true if nonzero, or None if NaN. Lists
+        // have no truth value.
+        let truth = |expr: &Expression| match eval(expr)? {
+            Value::List(_) => Err(EvalError::UnexpectedList),
+            n if n.is_nan() => Ok(None),
+            n => Ok(Some(!n.is_zero())),
+        };
+        // Gives 1 for true or 0 for false, and NaN for an unknown truth value,
+        // which is undefined in strict mode
+        let boolean = |b: Option<bool>| check(b.map_or(Value::Float(f64::NAN), Value::from), &[]);
+        // Compares the LHS and RHS for equality, which must have the same
+        // dimension
+        let equals = |lhs: &Expression, rhs: &Expression| {
+            let l = eval(lhs)?;
+            let r = like(&l, eval(rhs)?)?;
+            Ok((l.equals(&r), l, r))
+        };
+        // Evaluates a bitwise operation on integers, whose result wraps around
+        // rather than overflows in fixed-width mode
+        let bitwise = |l: Value, r: Value, name: &str, f: fn(&Value, &Value) -> Value| match (l, r)
+        {
+            (Value::Quantity(_, unit), _) | (_, Value::Quantity(_, unit)) => unexpected(&unit),
+            (l, _) if !l.is_integer() => domain(name, &l),
+            (_, r) if !r.is_integer() => domain(name, &r),
+            (l, r) => check(f(&l, &r).into_mode(env.mode()), &[&l, &r]),
+        };
+        // Evaluates a floating point function of the argument, using the complex
+        // variant for complex arguments
+        let float = |expr: &Expression, f: fn(f64) -> f64, c: fn(Complex64) -> Complex64| {
+            map_elements(eval(expr)?, &|n| match n {
+                Value::Quantity(_, unit) => unexpected(&unit),
+                n => check(n.map(f, c), &[&n]),
+            })
+        };
+        // Evaluates a floating point function with poles, i.e. finite arguments
+        // where it is infinite, which are outside of its domain in strict mode
+        let poles =
+            |name: &str, expr: &Expression, f: fn(f64) -> f64, c: fn(Complex64) -> Complex64| {
+                map_elements(eval(expr)?, &|n| match n {
+                    Value::Quantity(_, unit) => unexpected(&unit),
+                    n => match n.map(f, c) {
+                        v if strict && v.is_infinite() && !n.is_infinite() => domain(name, &n),
+                        v => check(v, &[&n]),
+                    },
+                })
+            };
+        // Evaluates a trigonometric function of an angle in the angle unit,
+        // using the complex variant in radians for complex arguments. Real
+        // results are only infinite at poles.
+        let trig = |name: &str,
+                    expr: &Expression,
+                    f: fn(Angle, f64) -> f64,
+                    c: fn(Complex64) -> Complex64| {
+            map_elements(eval(expr)?, &|n| match n {
+                Value::Quantity(_, unit) => unexpected(&unit),
+                Value::Complex(n) => check(c(n.scale(angle.to_radians(1.0))).into(), &[&n.into()]),
+                n => match f(angle, n.to_f64()) {
+                    v if strict && v.is_infinite() && !n.is_infinite() => domain(name, &n),
+                    v => check(Value::Float(v), &[&n]),
+                },
+            })
+        };
+        // Evaluates an inverse trigonometric function of the argument, giving
+        // an angle in the angle unit
+        let inverse = |expr: &Expression, f: fn(f64) -> f64, c: fn(Complex64) -> Complex64| {
+            map_elements(float(expr, f, c)?, &|n| {
+                Ok(match n {
+                    Value::Complex(n) => n.unscale(angle.to_radians(1.0)).into(),
+                    n => Value::Float(angle.from_radians(n.to_f64())),
+                })
+            })
+        };
+        // Rounds the argument to an integer with the given rounding mode
+        let round = |expr: &Expression, rounding: Rounding| {
+            map_elements(eval(expr)?, &|n| {
+                check(n.round_to(&Value::Integer(1.into()), rounding), &[&n])
+            })
+        };
+        // Evaluates a number theoretic function of integer arguments, which
+        // gives a result or the index of an argument outside of its domain.
+        // Floating point arguments must be below 2^53, above which they may have
+        // been rounded, and integer results are exact in any mode.
+        let integers = |name: &str,
+                        exprs: &[&Expression],
+                        f: fn(&[BigInt]) -> Result<Value, usize>| {
+            let values = exprs.iter().map(|expr| eval(expr)).collect::<Result<Vec<_>, _>>()?;
+            if let Some(unit) = values.iter().find_map(Value::unit) {
+                return unexpected(unit);
+            }
+            let inexact = |n: &Value| matches!(n, Value::Float(n) if n.abs() >= integer::MAX_EXACT);
+            if let Some(n) = values.iter().find(|n| !n.is_integer() || inexact(n)) {
+                return domain(name, n);
+            }
+            let integers: Vec<BigInt> = values.iter().filter_map(Value::truncate).collect();
+            match f(&integers) {
+                Ok(result) => {
+                    let checked = check(result.clone(), &values.iter().collect::<Vec<_>>())?;
+                    Ok(match (result, env.mode()) {
+                        (Value::Integer(n), Mode::Float) => Value::Integer(n),
+                        _ => checked,
+                    })
+                }
+                Err(i) => domain(name, &values[i]),
+            }
+        };
+        // Evaluates a value method of the argument
+        let method = |expr: &Expression, f: fn(&Value) -> Value| {
+            map_elements(eval(expr)?, &|n| check(f(&n), &[&n]))
+        };
+        // Evaluates a list, where a list was expected
+        let list = |expr: &Expression| match eval(expr)? {
+            Value::List(n) => Ok(n),
+            _ => Err(EvalError::ExpectedList),
+        };
+        // Checks for a dimensionless matrix, where a matrix was expected, which
+        // must be square if required. Returns its rows and the value itself.
+        let rows = |value: Value, square: bool| {
+            if let Some(unit) = find_unit(&value) {
+                return Err(EvalError::UnexpectedUnit { unit: unit.to_string() });
+            }
+            match matrix::rows(&value) {
+                Some(m) if square && m.len() != m[0].len() => {
+                    Err(EvalError::NotSquare { rows: m.len(), columns: m[0].len() })
+                }
+                Some(m) => Ok((m, value)),
+                None => Err(EvalError::ExpectedMatrix),
+            }
+        };
+        // Handles a singular matrix, where an inverse was expected
+        let singular = || match strict {
+            true => Err(EvalError::Singular),
+            false => Ok(Value::Float(f64::NAN)),
+        };
+        // Constructs a matrix with the given numbers of rows and columns, which
+        // must be positive integers
+        let construct = |name: &str, exprs: &[&Expression], f: fn(usize, usize) -> Matrix| {
+            let values = exprs.iter().map(|expr| eval(expr)).collect::<Result<Vec<_>, _>>()?;
+            if let Some(unit) = values.iter().find_map(Value::unit) {
+                return unexpected(unit);
+            }
+            let mut sizes = Vec::new();
+            for n in &values {
+                match n.truncate().filter(|_| n.is_integer()).and_then(|n| n.to_usize()) {
+                    Some(size) if size > 0 && size <= MAX_ELEMENTS => sizes.push(size),
+                    _ => return domain(name, n),
+                }
+            }
+            match sizes[0] * sizes[1] {
+                size if size > MAX_ELEMENTS => domain(name, &values[0]),
+                _ => check(matrix::to_value(f(sizes[0], sizes[1])), &[]),
+            }
+        };
         match self {
-            Expression::Add { lhs, rhs } => lhs.evaluate() + rhs.evaluate(),
-            Expression::Constant(c) => c.into(),
-            Expression::Cosine(expr) => expr.evaluate().cos(),
-            Expression::Degrees(expr) => expr.evaluate().to_degrees(),
-            Expression::Divide { lhs, rhs } => lhs.evaluate() / rhs.evaluate(),
-            Expression::Exponentiate { lhs, rhs } => lhs.evaluate().powf(rhs.evaluate()),
-            Expression::Factorial(expr) => match expr.evaluate() {
-                n if n == f64::INFINITY => n,
-                n if n < 0.0 || n.fract() != 0.0 => f64::NAN,
-                n => (1..=n.trunc() as i64).fold(1.0, |a, b| a * b as f64),
+            Expression::Absolute(expr) => method(expr, Value::abs),
+            Expression::Add { lhs, rhs } => zip_elements(eval(lhs)?, eval(rhs)?, &|l, r| {
+                let r = like(&l, r)?;
+                check(&l + &r, &[&l, &r])
+            }),
+            Expression::Aggregate { function, args } => {
+                let args = args.iter().map(eval).collect::<Result<Vec<_>, _>>()?;
+                // Lists are flattened into their elements, e.g. sum([1, 2], 3)
+                let mut values = Vec::new();
+                flatten(args, &mut values);
+                // The percentage of a percentile is dimensionless, while the
+                // values are converted to the unit of the first
+                let start = match function {
+                    Aggregate::Percentile => 1,
+                    _ => 0,
+                };
+                if let Some(unit) = values[..start].iter().find_map(Value::unit) {
+                    return unexpected(unit);
+                }
+                if let Some(first) = values.get(start).cloned() {
+                    for value in &mut values[start..] {
+                        *value = like(&first, value.clone())?;
+                    }
+                }
+                match function.apply(&values) {
+                    Ok(result) => check(result, &values.iter().collect::<Vec<_>>()),
+                    // Complex numbers can't be ordered, as in comparisons
+                    Err(i) if strict && values[i].is_complex() => Err(EvalError::NotReal),
+                    Err(i) => domain(function.name(), &values[i]),
+                }
+            }
+            Expression::And { lhs, rhs } => match truth(lhs)? {
+                Some(false) => boolean(Some(false)),
+                l => boolean(l.and(truth(rhs)?)),
+            },
+            Expression::ArcCosine(expr) => inverse(expr, f64::acos, |c| c.acos()),
+            Expression::ArcSine(expr) => inverse(expr, f64::asin, |c| c.asin()),
+            Expression::ArcTangent(expr) => inverse(expr, f64::atan, |c| c.atan()),
+            Expression::ArcTangent2 { y, x } => {
+                let y = eval(y)?;
+                match like(&y, eval(x)?)? {
+                    x if y.is_complex() || x.is_complex() => domain("atan2", &x),
+                    x => check(
+                        Value::Float(angle.from_radians(y.to_f64().atan2(x.to_f64()))),
+                        &[&y, &x],
+                    ),
+                }
+            }
+            Expression::Argument(expr) => method(expr, Value::arg),
+            Expression::BinaryLogarithm(expr) => poles("log2", expr, f64::log2, |c| c.log(2.0)),
+            Expression::Binomial { n, k } => integers("nCr", &[n, k], |n| match (&n[0], &n[1]) {
+                (n, _) if n.is_negative() => Err(0),
+                (_, k) if k.is_negative() => Err(1),
+                (n, k) => Ok(integer::binomial(n, k)),
+            }),
+            Expression::BitAnd { lhs, rhs } => bitwise(eval(lhs)?, eval(rhs)?, "&", Value::bit_and),
+            Expression::BitNot(expr) => match eval(expr)? {
+                Value::Quantity(_, unit) => unexpected(&unit),
+                n if !n.is_integer() => domain("~", &n),
+                n => check(n.bit_not().into_mode(env.mode()), &[&n]),
+            },
+            Expression::BitOr { lhs, rhs } => bitwise(eval(lhs)?, eval(rhs)?, "|", Value::bit_or),
+            Expression::BitXor { lhs, rhs } => {
+                bitwise(eval(lhs)?, eval(rhs)?, "xor", Value::bit_xor)
+            }
+            Expression::Call { function, args } => {
+                let args = args.iter().map(eval).collect::<Result<Vec<_>, _>>()?;
+                match function.as_ref() {
+                    Function::Native { func, .. } => match args.iter().find_map(Value::unit) {
+                        Some(unit) => unexpected(unit),
+                        None => {
+                            let floats: Vec<f64> = args.iter().map(Value::to_f64).collect();
+                            check(Value::Float(func(&floats)), &args.iter().collect::<Vec<_>>())
+                        }
+                    },
+                    Function::User { body, .. } => body.evaluate_with(env, &args, strict),
+                }
+            }
+            Expression::Ceiling(expr) => round(expr, Rounding::Ceiling),
+            Expression::CommonLogarithm(expr) => poles("log10", expr, f64::log10, |c| c.log(10.0)),
+            Expression::Conjugate(expr) => method(expr, Value::conj),
+            Expression::Constant(c) => {
+                let value = Value::from(c);
+                check(value.clone(), &[&value])
+            }
+            Expression::Convert { value, unit } => {
+                let n = eval(value)?;
+                match n.convert(unit) {
+                    Some(converted) => check(converted, &[&n]),
+                    None => Err(EvalError::IncompatibleUnits {
+                        lhs: n.unit().cloned().unwrap_or_default().to_string(),
+                        rhs: unit.to_string(),
+                    }),
+                }
+            }
+            Expression::ConvertBase { value, .. } => eval(value),
+            Expression::Cosecant(expr) => {
+                trig("csc", expr, |a, n| 1.0 / a.sin(n), |c| c.sin().inv())
+            }
+            Expression::Cosine(expr) => trig("cos", expr, Angle::cos, |c| c.cos()),
+            Expression::Cotangent(expr) => {
+                trig("cot", expr, |a, n| 1.0 / a.tan(n), |c| c.tan().inv())
+            }
+            Expression::CrossProduct { lhs, rhs } => {
+                let (l, r) = (list(lhs)?, list(rhs)?);
+                if l.len() != r.len() {
+                    return Err(EvalError::LengthMismatch { lhs: l.len(), rhs: r.len() });
+                }
+                if l.len() != 3 {
+                    return domain("cross", &Value::from(l.len() as i64));
+                }
+                let term = |i: usize, j: usize| &(&l[i] * &r[j]) - &(&l[j] * &r[i]);
+                let product = Value::List(vec![term(1, 2), term(2, 0), term(0, 1)]);
+                check(product, &[&Value::List(l.clone()), &Value::List(r.clone())])
+            }
+            Expression::CubeRoot(expr) => map_elements(eval(expr)?, &|n| match n {
+                Value::Quantity(_, unit) => unexpected(&unit),
+                n => check(n.root(&Value::from(3)), &[&n]),
+            }),
+            Expression::DegreeAngle(expr) => match eval(expr)? {
+                Value::Quantity(_, unit) => unexpected(&unit),
+                n if angle == Angle::Degrees => Ok(n),
+                n => check(&n * &Value::Float(angle.turn() / 360.0), &[&n]),
             },
+            Expression::Degrees(expr) => float(expr, f64::to_degrees, |c| c * f64::to_degrees(1.0)),
+            Expression::Determinant(expr) => {
+                let (m, n) = rows(eval(expr)?, true)?;
+                check(matrix::determinant(&m), &[&n])
+            }
+            Expression::Divide { lhs, rhs } => {
+                zip_elements(eval(lhs)?, eval(rhs)?, &|l, r| match (l, r) {
+                    (_, r) if strict && r.is_zero() => Err(EvalError::DivisionByZero),
+                    (l, r) => check(l.divide(&r), &[&l, &r]),
+                })
+            }
+            Expression::DotProduct { lhs, rhs } => {
+                let (l, r) = (list(lhs)?, list(rhs)?);
+                if l.len() != r.len() {
+                    return Err(EvalError::LengthMismatch { lhs: l.len(), rhs: r.len() });
+                }
+                let product = l.iter().zip(&r).fold(Value::from(0), |sum, (l, r)| &sum + &(l * r));
+                check(product, &[&Value::List(l.clone()), &Value::List(r.clone())])
+            }
+            Expression::DoubleFactorial(expr) => integers("!!", &[expr], |n| match &n[0] {
+                n if n.is_negative() => Err(0),
+                n => Ok(integer::double_factorial(n)),
+            }),
+            Expression::Eigenvalues(expr) => {
+                let (m, n) = rows(eval(expr)?, true)?;
+                // Complex matrices may have complex eigenvalues
+                if let Some(c) = m.iter().flatten().find(|n| n.is_complex()) {
+                    return match strict {
+                        true => Err(EvalError::NotReal),
+                        false => domain("eig", c),
+                    };
+                }
+                if !matrix::is_symmetric(&m) {
+                    return Err(EvalError::NotSymmetric);
+                }
+                let values = matrix::eigenvalues(&m).into_iter().map(Value::Float).collect();
+                check(Value::List(values), &[&n])
+            }
+            Expression::Equal { lhs, rhs } => {
+                let (equal, l, r) = equals(lhs, rhs)?;
+                check(equal.into(), &[&l, &r])
+            }
+            Expression::Exponential(expr) => float(expr, f64::exp, |c| c.exp()),
+            Expression::ExponentialMinusOne(expr) => float(expr, f64::exp_m1, |c| c.exp() - 1.0),
+            Expression::Exponentiate { lhs, rhs } => {
+                let (l, r) = (eval(lhs)?, eval(rhs)?);
+                if matrix::rows(&l).is_some() {
+                    // Powers of square matrices are repeated matrix products, of
+                    // the inverse for negative exponents
+                    let (m, l) = rows(l, true)?;
+                    if let Some(unit) = r.unit() {
+                        return unexpected(unit);
+                    }
+                    let e = match r.truncate() {
+                        Some(e) if r.is_integer() => e,
+                        _ => return domain("^", &r),
+                    };
+                    let m = match e.is_negative() {
+                        true => match matrix::inverse(&m) {
+                            Some(inverse) => inverse,
+                            None => return singular(),
+                        },
+                        false => m,
+                    };
+                    return check(matrix::to_value(matrix::power(&m, &e.abs())), &[&l, &r]);
+                }
+                zip_elements(l, r, &|l, r| match (l, r) {
+                    (_, Value::Quantity(_, unit)) => unexpected(&unit),
+                    (l @ Value::Quantity(..), r) if l.unit_pow(&r).is_none() => {
+                        Err(EvalError::NonIntegerPower { quantity: l.to_string() })
+                    }
+                    (l, r) if strict && l.is_zero() && r.is_negative() => {
+                        Err(EvalError::DivisionByZero)
+                    }
+                    (l, r) => check(l.pow(&r), &[&l, &r]),
+                })
+            }
+            Expression::Factorial(expr) => match eval(expr)? {
+                Value::Quantity(_, unit) => unexpected(&unit),
+                n if n.is_complex() || n.is_nan() => domain("factorial", &n),
+                n if n.is_negative() && (n.is_integer() || n.is_infinite()) => {
+                    domain("factorial", &n)
+                }
+                n => check(n.factorial(), &[&n]),
+            },
+            Expression::Factorize(expr) => integers("factor", &[expr], |n| {
+                // Factorizes on display, but fails here if it can't
+                integer::factorize(&n[0]).map(|_| n[0].clone().into()).ok_or(0)
+            }),
+            Expression::Floor(expr) => round(expr, Rounding::Floor),
+            Expression::FractionalPart(expr) => method(expr, Value::fract),
+            Expression::Gamma(expr) => match eval(expr)? {
+                Value::Quantity(_, unit) => unexpected(&unit),
+                n if n.is_complex() || n.is_nan() => domain("gamma", &n),
+                n if (n.is_integer() || n.is_infinite()) && (n.is_negative() || n.is_zero()) => {
+                    domain("gamma", &n)
+                }
+                n => check(Value::Float(gamma::gamma(n.to_f64())), &[&n]),
+            },
+            Expression::GreatestCommonDivisor { lhs, rhs } => {
+                integers("gcd", &[lhs, rhs], |n| Ok(n[0].gcd(&n[1]).into()))
+            }
+            Expression::Greater { lhs, rhs } => compare(lhs, rhs, Ordering::is_gt),
+            Expression::GreaterEqual { lhs, rhs } => compare(lhs, rhs, Ordering::is_ge),
+            Expression::HyperbolicCosine(expr) => float(expr, f64::cosh, |c| c.cosh()),
+            Expression::HyperbolicSine(expr) => float(expr, f64::sinh, |c| c.sinh()),
+            Expression::HyperbolicTangent(expr) => float(expr, f64::tanh, |c| c.tanh()),
+            Expression::Hypotenuse { lhs, rhs } => {
+                let l = eval(lhs)?;
+                let r = like(&l, eval(rhs)?)?;
+                check(l.hypot(&r), &[&l, &r])
+            }
+            Expression::Identity(expr) => {
+                construct("identity", &[expr, expr], |n, _| matrix::identity(n))
+            }
+            Expression::If { condition, then, otherwise } => match truth(condition)? {
+                Some(true) => eval(then),
+                Some(false) => eval(otherwise),
+                None => boolean(None),
+            },
+            Expression::Imaginary(expr) => method(expr, Value::im),
+            Expression::Index { value, index } => {
+                let n = list(value)?;
+                let i = eval(index)?;
+                if let Some(unit) = i.unit() {
+                    return unexpected(unit);
+                }
+                // Negative indices count from the end, e.g. -1 is the last element
+                let len = BigInt::from(n.len());
+                let position =
+                    i.truncate().filter(|_| i.is_integer()).map(|i| match i.is_negative() {
+                        true => i + &len,
+                        false => i,
+                    });
+                match position.filter(|i| !i.is_negative() && *i < len).and_then(|i| i.to_usize()) {
+                    Some(i) => check(n[i].clone(), &[&n[i]]),
+                    None => Err(EvalError::InvalidIndex { index: i.to_f64(), length: n.len() }),
+                }
+            }
+            Expression::InverseHyperbolicCosine(expr) => float(expr, f64::acosh, |c| c.acosh()),
+            Expression::InverseHyperbolicSine(expr) => float(expr, f64::asinh, |c| c.asinh()),
+            Expression::InverseHyperbolicTangent(expr) => {
+                poles("atanh", expr, f64::atanh, |c| c.atanh())
+            }
+            Expression::IsPrime(expr) => integers("isprime", &[expr], |n| match &n[0] {
+                n if n.bits() > integer::MAX_PRIME_BITS => Err(0),
+                n => Ok(integer::is_prime(n).into()),
+            }),
+            Expression::LeastCommonMultiple { lhs, rhs } => {
+                integers("lcm", &[lhs, rhs], |n| Ok(n[0].lcm(&n[1]).into()))
+            }
+            Expression::Length(expr) => {
+                Ok(Value::from(list(expr)?.len() as i64).into_mode(env.mode()))
+            }
+            Expression::Less { lhs, rhs } => compare(lhs, rhs, Ordering::is_lt),
+            Expression::LessEqual { lhs, rhs } => compare(lhs, rhs, Ordering::is_le),
+            Expression::List(exprs) => {
+                let n = exprs.iter().map(eval).collect::<Result<Vec<_>, _>>()?;
+                Ok(Value::List(n))
+            }
+            Expression::Logarithm { value, base } => match (eval(value)?, eval(base)?) {
+                (Value::Quantity(_, unit), _) | (_, Value::Quantity(_, unit)) => unexpected(&unit),
+                (_, b) if strict && (b.is_zero() || b.equals(&Value::from(1))) => domain("log", &b),
+                (n, _) if strict && n.is_zero() => domain("log", &n),
+                (n, b) => {
+                    // Common bases use the more accurate functions, e.g. for log(1000, 10)
+                    let ln = |n: &Value| n.map(f64::ln, |c| c.ln());
+                    let log = match b.to_f64() {
+                        _ if b.is_complex() => ln(&n).divide(&ln(&b)),
+                        2.0 => n.map(f64::log2, |c| c.log(2.0)),
+                        10.0 => n.map(f64::log10, |c| c.log(10.0)),
+                        _ => ln(&n).divide(&ln(&b)),
+                    };
+                    check(log, &[&n, &b])
+                }
+            },
+            Expression::LogarithmOnePlus(expr) => {
+                poles("log1p", expr, f64::ln_1p, |c| (c + 1.0).ln())
+            }
+            Expression::LogGamma(expr) => match eval(expr)? {
+                Value::Quantity(_, unit) => unexpected(&unit),
+                n if n.is_complex() || n.is_nan() => domain("lgamma", &n),
+                n if n.is_integer() && (n.is_negative() || n.is_zero()) => domain("lgamma", &n),
+                n => check(Value::Float(gamma::lgamma(n.to_f64())), &[&n]),
+            },
+            Expression::MatrixInverse(expr) => {
+                let (m, n) = rows(eval(expr)?, true)?;
+                match matrix::inverse(&m) {
+                    Some(inverse) => check(matrix::to_value(inverse), &[&n]),
+                    None => singular(),
+                }
+            }
+            Expression::ModularInverse { value, modulus } => {
+                integers("modinv", &[value, modulus], |n| match (&n[0], &n[1]) {
+                    (_, m) if !m.is_positive() => Err(1),
+                    (a, m) => integer::mod_inverse(a, m).map(Value::from).ok_or(0),
+                })
+            }
+            Expression::ModularPower { base, exponent, modulus } => {
+                integers("modpow", &[base, exponent, modulus], |n| match (&n[0], &n[1], &n[2]) {
+                    (_, _, m) if !m.is_positive() => Err(2),
+                    (b, e, m) if e.is_negative() => match integer::mod_inverse(b, m) {
+                        Some(inverse) => Ok(inverse.modpow(&-e, m).into()),
+                        None => Err(0),
+                    },
+                    (b, e, m) => Ok(b.modpow(e, m).into()),
+                })
+            }
             Expression::Modulo { lhs, rhs } => {
-                // The % operator in Rust is remainder, not modulo
-                let l = lhs.evaluate();
-                let r = rhs.evaluate();
-                ((l % r) + r) % r
-            }
-            Expression::Multiply { lhs, rhs } => lhs.evaluate() * rhs.evaluate(),
-            Expression::Negate(expr) => -expr.evaluate(),
-            Expression::Number(n) => *n,
-            Expression::Radians(expr) => expr.evaluate().to_radians(),
-            Expression::Round { value, decimals } => {
-                let n = value.evaluate();
-                let d = decimals.evaluate();
-                if d < 0.0 || d.fract() != 0.0 {
-                    return f64::NAN;
+                zip_elements(eval(lhs)?, eval(rhs)?, &|l, r| match like(&l, r)? {
+                    r if strict && r.is_zero() => Err(EvalError::DivisionByZero),
+                    r => check(l.modulo(&r), &[&l, &r]),
+                })
+            }
+            Expression::Multiply { lhs, rhs } => {
+                let (l, r) = (eval(lhs)?, eval(rhs)?);
+                let product = matrix::product(&l, &r);
+                if let Some(unit) = product.as_ref().and(find_unit(&l).or_else(|| find_unit(&r))) {
+                    return unexpected(unit);
+                }
+                match product {
+                    Some(Ok(product)) => check(product, &[&l, &r]),
+                    Some(Err((lhs, rhs))) => Err(EvalError::LengthMismatch { lhs, rhs }),
+                    None => zip_elements(l, r, &|l, r| check(&l * &r, &[&l, &r])),
+                }
+            }
+            Expression::NaturalLogarithm(expr) => poles("ln", expr, f64::ln, |c| c.ln()),
+            Expression::Negate(expr) => map_elements(eval(expr)?, &|n| check(-&n, &[&n])),
+            Expression::NextPrime(expr) => integers("nextprime", &[expr], |n| {
+                integer::next_prime(&n[0]).map(Value::from).ok_or(0)
+            }),
+            Expression::Norm(expr) => {
+                let n = list(expr)?;
+                let squares = n.iter().map(Value::abs).map(|n| &n * &n);
+                let norm = squares.fold(Value::from(0), |sum, n| &sum + &n).sqrt();
+                check(norm, &[&Value::List(n)])
+            }
+            Expression::Not(expr) => boolean(truth(expr)?.map(|b| !b)),
+            Expression::NotEqual { lhs, rhs } => {
+                let (equal, l, r) = equals(lhs, rhs)?;
+                check((!equal).into(), &[&l, &r])
+            }
+            Expression::Number(n) => check(n.clone(), &[n]),
+            Expression::Or { lhs, rhs } => match truth(lhs)? {
+                Some(true) => boolean(Some(true)),
+                l => boolean(l.and(truth(rhs)?)),
+            },
+            Expression::Parameter(i) => {
+                Ok(params.get(*i).cloned().unwrap_or(Value::Float(f64::NAN)))
+            }
+            Expression::Permutations { n, k } => {
+                integers("nPr", &[n, k], |n| match (&n[0], &n[1]) {
+                    (n, _) if n.is_negative() => Err(0),
+                    (_, k) if k.is_negative() => Err(1),
+                    (n, k) => Ok(integer::permutations(n, k)),
+                })
+            }
+            Expression::Radians(expr) => float(expr, f64::to_radians, |c| c * f64::to_radians(1.0)),
+            Expression::Rank(expr) => {
+                let (m, _) = rows(eval(expr)?, false)?;
+                Ok(Value::from(matrix::rank(&m) as i64).into_mode(env.mode()))
+            }
+            Expression::Real(expr) => method(expr, Value::re),
+            Expression::Root { value, degree } => match (eval(value)?, eval(degree)?) {
+                (Value::Quantity(_, unit), _) | (_, Value::Quantity(_, unit)) => unexpected(&unit),
+                (_, d) if strict && d.is_zero() => domain("nthroot", &d),
+                (n, d) => check(n.root(&d), &[&n, &d]),
+            },
+            Expression::Round { value, decimals, rounding } => {
+                zip_elements(eval(value)?, eval(decimals)?, &|n, d| match (n, d) {
+                    (_, Value::Quantity(_, unit)) => unexpected(&unit),
+                    (_, d) if d.is_negative() || !d.is_integer() => domain("round", &d),
+                    (n, d) => check(n.round(&d, *rounding), &[&n, &d]),
+                })
+            }
+            Expression::RoundTo { value, step, rounding } => {
+                zip_elements(eval(value)?, eval(step)?, &|n, s| {
+                    // Quantities can be rounded to a plain step in their own unit
+                    let step = match s {
+                        s if s.unit().is_none() => s,
+                        s => like(&n, s)?,
+                    };
+                    match step {
+                        s if s.is_zero() || s.is_complex() => domain("roundto", &s),
+                        s => check(n.round_to(&s, *rounding), &[&n, &s]),
+                    }
+                })
+            }
+            Expression::Secant(expr) => trig("sec", expr, |a, n| 1.0 / a.cos(n), |c| c.cos().inv()),
+            Expression::ShiftLeft { lhs, rhs } => match (eval(lhs)?, eval(rhs)?) {
+                (_, r) if r.is_negative() => domain("<<", &r),
+                (l, r) => bitwise(l, r, "<<", Value::shl),
+            },
+            Expression::ShiftRight { lhs, rhs } => match (eval(lhs)?, eval(rhs)?) {
+                (_, r) if r.is_negative() => domain(">>", &r),
+                (l, r) => bitwise(l, r, ">>", Value::shr),
+            },
+            Expression::Sign(expr) => method(expr, Value::sign),
+            Expression::Sine(expr) => trig("sin", expr, Angle::sin, |c| c.sin()),
+            Expression::Solve { matrix: a, rhs } => {
+                let (m, n) = rows(eval(a)?, true)?;
+                let b = eval(rhs)?;
+                if let Some(unit) = find_unit(&b) {
+                    return unexpected(unit);
+                }
+                // A vector is a column, whose solution is a vector
+                let (columns, vector) = match (matrix::rows(&b), matrix::vector(&b)) {
+                    (Some(b), _) => (b, false),
+                    (None, Some(b)) => (b.into_iter().map(|n| vec![n]).collect(), true),
+                    (None, None) => return Err(EvalError::ExpectedMatrix),
                 };
-                let scale = 10_f64.powf(d);
-                (scale * n).round() / scale
+                if columns.len() != m.len() {
+                    return Err(EvalError::LengthMismatch { lhs: m.len(), rhs: columns.len() });
+                }
+                match matrix::solve(&m, &columns) {
+                    Some(x) if vector => check(Value::List(x.concat()), &[&n, &b]),
+                    Some(x) => check(matrix::to_value(x), &[&n, &b]),
+                    None => singular(),
+                }
+            }
+            Expression::SquareRoot(expr) => map_elements(eval(expr)?, &|n| match n {
+                Value::Quantity(_, unit) if unit.sqrt().is_none() => unexpected(&unit),
+                n => check(n.sqrt(), &[&n]),
+            }),
+            Expression::Subtract { lhs, rhs } => zip_elements(eval(lhs)?, eval(rhs)?, &|l, r| {
+                let r = like(&l, r)?;
+                check(&l - &r, &[&l, &r])
+            }),
+            Expression::Tangent(expr) => trig("tan", expr, Angle::tan, |c| c.tan()),
+            Expression::Totient(expr) => integers("totient", &[expr], |n| match &n[0] {
+                n if !n.is_positive() => Err(0),
+                n => integer::totient(n).map(Value::from).ok_or(0),
+            }),
+            Expression::Transpose(expr) => {
+                let n = eval(expr)?;
+                // A vector is a row, whose transpose is a column
+                let row = matrix::vector(&n).filter(|row| !row.is_empty()).map(|row| vec![row]);
+                match matrix::rows(&n).or(row) {
+                    Some(m) => check(matrix::to_value(matrix::transpose(&m)), &[&n]),
+                    None => Err(EvalError::ExpectedMatrix),
+                }
             }
-            Expression::Sine(expr) => expr.evaluate().sin(),
-            Expression::SquareRoot(expr) => expr.evaluate().sqrt(),
-            Expression::Subtract { lhs, rhs } => lhs.evaluate() - rhs.evaluate(),
-            Expression::Tangent(expr) => expr.evaluate().tan(),
+            Expression::Truncate(expr) => round(expr, Rounding::Down),
+            Expression::Variable(name) => match env.value(name) {
+                Some(value) => check(value.clone(), &[value]),
+                None if strict => Err(EvalError::UnknownVariable { name: name.clone() }),
+                None => Ok(Value::Float(f64::NAN)),
+            },
+            Expression::Zeros { rows: m, columns: n } => construct("zeros", &[m, n], matrix::zeros),
+        }
+    }
+}
+
+/// Returns the unit of a quantity, or of the first quantity in a list
+fn find_unit(n: &Value) -> Option<&Unit> {
+    match n {
+        Value::List(n) => n.iter().find_map(find_unit),
+        n => n.unit(),
+    }
+}
+
+/// Flattens values into a vector, replacing lists with their elements
+fn flatten(values: Vec<Value>, into: &mut Vec<Value>) {
+    for value in values {
+        match value {
+            Value::List(n) => flatten(n, into),
+            n => into.push(n),
+        }
+    }
+}
+
+/// Applies an operation to a value, or to each element if it is a list
+fn map_elements<F>(n: Value, f: &F) -> Result<Value, EvalError>
+where
+    F: Fn(Value) -> Result<Value, EvalError>,
+{
+    match n {
+        Value::List(n) => {
+            n.into_iter().map(|n| map_elements(n, f)).collect::<Result<_, _>>().map(Value::List)
+        }
+        n => f(n),
+    }
+}
+
+/// Applies a binary operation to values, or element-wise if either is a list.
+/// Lists must have the same length, while a value and a list apply the
+/// operation to the value and each element.
+fn zip_elements<F>(l: Value, r: Value, f: &F) -> Result<Value, EvalError>
+where
+    F: Fn(Value, Value) -> Result<Value, EvalError>,
+{
+    match (l, r) {
+        (Value::List(l), Value::List(r)) if l.len() != r.len() => {
+            Err(EvalError::LengthMismatch { lhs: l.len(), rhs: r.len() })
         }
+        (Value::List(l), Value::List(r)) => l
+            .into_iter()
+            .zip(r)
+            .map(|(l, r)| zip_elements(l, r, f))
+            .collect::<Result<_, _>>()
+            .map(Value::List),
+        (Value::List(l), r) => l
+            .into_iter()
+            .map(|l| zip_elements(l, r.clone(), f))
+            .collect::<Result<_, _>>()
+            .map(Value::List),
+        (l, Value::List(r)) => r
+            .into_iter()
+            .map(|r| zip_elements(l.clone(), r, f))
+            .collect::<Result<_, _>>()
+            .map(Value::List),
+        (l, r) => f(l, r),
     }
 }
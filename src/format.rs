@@ -0,0 +1,322 @@
+use num::bigint::BigInt;
+use num::rational::BigRational;
+use num::{One, Signed, Zero};
+
+use crate::integer;
+use crate::value::Value;
+
+/// The number of significant digits shown for fractions in scientific and
+/// engineering notation, which is enough to represent any f64
+const FRACTION_DIGITS: usize = 17;
+
+/// SI prefixes for engineering notation, from 10^-24 to 10^24
+const PREFIXES: [&str; 17] =
+    ["y", "z", "a", "f", "p", "n", "µ", "m", "", "k", "M", "G", "T", "P", "E", "Z", "Y"];
+
+/// A notation for displaying real numbers
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Notation {
+    /// Engineering notation, with exponents that are multiples of three shown
+    /// as SI prefixes, e.g. 12.3k
+    Engineering,
+    /// Fixed-point notation with the given number of decimals, e.g. 3.14
+    Fixed(usize),
+    /// The shortest representation of the value, e.g. 1234.5 or 7/2
+    #[default]
+    General,
+    /// Scientific notation, e.g. 1.2345e3
+    Scientific,
+    /// Rounded to the given number of significant digits, e.g. 1230
+    Significant(usize),
+}
+
+/// Formats values as text, with a given base, notation, and other options
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Formatter {
+    /// The base from 2 to 36, where bases other than 10 ignore the notation
+    pub base: u32,
+    /// Displays integers as their prime factorization, e.g. 2^3 * 3 for 24
+    pub factors: bool,
+    /// Displays fractions as mixed numbers in general notation, e.g. 3 1/2
+    pub mixed: bool,
+    /// The notation of real numbers
+    pub notation: Notation,
+    /// Groups the digits of integer parts by thousands, e.g. 1,234,567
+    pub separators: bool,
+}
+
+impl Default for Formatter {
+    fn default() -> Self {
+        Self {
+            base: 10,
+            factors: false,
+            mixed: false,
+            notation: Notation::General,
+            separators: false,
+        }
+    }
+}
+
+impl Formatter {
+    /// Formats a value as text
+    pub fn format(&self, value: &Value) -> String {
+        if let Some(n) = value.truncate().filter(|_| self.factors && value.is_integer()) {
+            return self.factorization(&n);
+        }
+        match (self.base, self.separators) {
+            (10, true) => Self::group(&self.format_value(value)),
+            (10, false) => self.format_value(value),
+            (base, _) => value.to_string_radix(base),
+        }
+    }
+
+    /// Formats an integer as its prime factorization, with the primes in the
+    /// base and the exponents in decimal, e.g. -2^3 * 3 for -24. Integers
+    /// without prime factors, i.e. 0 and ±1, or too large to factorize, are
+    /// formatted as they are.
+    fn factorization(&self, n: &BigInt) -> String {
+        let formatter = Formatter { factors: false, ..*self };
+        let factors = integer::factorize(n).unwrap_or_default();
+        if factors.is_empty() {
+            return formatter.format(&Value::Integer(n.clone()));
+        }
+        let factors: Vec<String> = factors
+            .into_iter()
+            .map(|(p, e)| match (formatter.format(&Value::Integer(p)), e) {
+                (p, 1) => p,
+                (p, e) => format!("{}^{}", p, e),
+            })
+            .collect();
+        match n.is_negative() {
+            true => format!("-{}", factors.join(" * ")),
+            false => factors.join(" * "),
+        }
+    }
+
+    /// Formats a value in the notation, without digit separators
+    fn format_value(&self, value: &Value) -> String {
+        match value {
+            Value::Complex(n) if self.notation != Notation::General => {
+                let re = self.format_real(&Value::Float(n.re));
+                let im = self.format_real(&Value::Float(n.im));
+                match im.starts_with('-') {
+                    true => format!("{}{}i", re, im),
+                    false => format!("{}+{}i", re, im),
+                }
+            }
+            Value::List(n) => {
+                let n: Vec<String> = n.iter().map(|n| self.format_value(n)).collect();
+                format!("[{}]", n.join(", "))
+            }
+            Value::Quantity(n, unit) => format!("{} {}", self.format_value(n), unit),
+            n => self.format_real(n),
+        }
+    }
+
+    /// Formats a real value in the notation
+    fn format_real(&self, value: &Value) -> String {
+        let ratio = match value.to_ratio() {
+            _ if self.notation == Notation::General => None,
+            Some(ratio) => Some(ratio),
+            None => BigRational::from_float(value.to_f64()),
+        };
+        match (self.notation, ratio) {
+            (_, None) if self.mixed => format!("{:#}", value),
+            (_, None) => value.to_string(),
+            (Notation::Engineering, Some(_)) => Digits::shortest(value).engineering(),
+            (Notation::Fixed(decimals), Some(n)) => Digits::fixed(&n, decimals),
+            (Notation::General, Some(_)) => value.to_string(),
+            (Notation::Scientific, Some(_)) => Digits::shortest(value).scientific(),
+            (Notation::Significant(precision), Some(n)) => {
+                let digits = Digits::significant(&n, precision);
+                match digits.exponent {
+                    e if (-6..21).contains(&e) => digits.positional(),
+                    _ => digits.scientific(),
+                }
+            }
+        }
+    }
+
+    /// Groups the digits of integer parts by thousands, skipping fractional
+    /// digits and exponents, e.g. 1234567.891 to 1,234,567.891
+    fn group(text: &str) -> String {
+        let chars: Vec<char> = text.chars().collect();
+        let mut result = String::new();
+        let mut i = 0;
+        while i < chars.len() {
+            let start = i;
+            while i < chars.len() && chars[i].is_ascii_digit() {
+                i += 1;
+            }
+            if start == i {
+                result.push(chars[i]);
+                i += 1;
+                continue;
+            }
+            // The characters before the digits, e.g. the . of a fraction or the
+            // e- of an exponent
+            let before = |n: usize| start.checked_sub(n).map(|j| chars[j]);
+            let skip = matches!(before(1), Some('.') | Some('e') | Some('^'))
+                || (matches!(before(1), Some('-') | Some('+'))
+                    && matches!(before(2), Some('e') | Some('^')));
+            for (j, c) in chars[start..i].iter().enumerate() {
+                if !skip && j > 0 && (i - start - j) % 3 == 0 {
+                    result.push(',');
+                }
+                result.push(*c);
+            }
+        }
+        result
+    }
+}
+
+/// The significant decimal digits of a number, with the value
+/// ±d.ddd * 10^exponent
+struct Digits {
+    negative: bool,
+    digits: String,
+    exponent: i64,
+}
+
+impl Digits {
+    /// Formats a rational number with the given number of decimals, rounding
+    /// half-way cases away from zero
+    fn fixed(n: &BigRational, decimals: usize) -> String {
+        let scaled = (n.abs() * Self::pow10(decimals as i64)).round().to_integer();
+        let sign = if n.is_negative() && !scaled.is_zero() { "-" } else { "" };
+        let digits = format!("{:0>width$}", scaled, width = decimals + 1);
+        let (int, fract) = digits.split_at(digits.len() - decimals);
+        match decimals {
+            0 => format!("{}{}", sign, int),
+            _ => format!("{}{}.{}", sign, int, fract),
+        }
+    }
+
+    /// Parses the digits of a plain or scientific decimal string, e.g. 0.0123
+    /// or 1.23e-2, removing leading and trailing zeros
+    fn parse(text: &str) -> Self {
+        let (negative, text) = match text.strip_prefix('-') {
+            Some(text) => (true, text),
+            None => (false, text),
+        };
+        let (mantissa, exponent) = match text.find('e') {
+            Some(i) => (&text[..i], text[i + 1..].parse().unwrap_or(0)),
+            None => (text, 0),
+        };
+        let point = mantissa.find('.').unwrap_or(mantissa.len()) as i64;
+        let digits = mantissa.replace('.', "");
+        let leading = digits.len() - digits.trim_start_matches('0').len();
+        let digits = digits.trim_matches('0');
+        match digits.is_empty() {
+            true => Self { negative, digits: "0".into(), exponent: 0 },
+            false => Self {
+                negative,
+                digits: digits.to_string(),
+                exponent: point - 1 - leading as i64 + exponent,
+            },
+        }
+    }
+
+    /// Returns the shortest digits which represent the value, i.e. all digits
+    /// of integers, decimals and floats, or enough digits for fractions
+    fn shortest(value: &Value) -> Self {
+        match value {
+            Value::Float(n) => Self::parse(&format!("{:e}", n)),
+            Value::Rational(n) => {
+                let mut digits = Self::significant(n, FRACTION_DIGITS);
+                digits.digits = digits.digits.trim_end_matches('0').to_string();
+                digits
+            }
+            n => Self::parse(&n.to_string()),
+        }
+    }
+
+    /// Rounds a rational number to the given number of significant digits,
+    /// rounding half-way cases away from zero
+    fn significant(n: &BigRational, precision: usize) -> Self {
+        let precision = precision.max(1);
+        if n.is_zero() {
+            return Self { negative: false, digits: "0".repeat(precision), exponent: 0 };
+        }
+        // The exponent is within one of the difference in digits
+        let abs = n.abs();
+        let digits = |n: &BigInt| n.to_string().len() as i64;
+        let mut exponent = digits(abs.numer()) - digits(abs.denom());
+        if abs < Self::pow10(exponent) {
+            exponent -= 1;
+        }
+        let scale = Self::pow10(precision as i64 - 1 - exponent);
+        let mut mantissa = (abs * scale).round().to_integer();
+        // Rounding may carry into another digit, e.g. 9.99 to 10.0
+        if mantissa.to_string().len() > precision {
+            mantissa /= 10;
+            exponent += 1;
+        }
+        Self { negative: n.is_negative(), digits: mantissa.to_string(), exponent }
+    }
+
+    /// Formats the digits in engineering notation, with an SI prefix for the
+    /// exponent, e.g. 12.3k
+    fn engineering(&self) -> String {
+        let exponent = self.exponent.div_euclid(3) * 3;
+        let (int, fract) = self.split((self.exponent - exponent) as usize + 1);
+        let mantissa = match fract.is_empty() {
+            true => format!("{}{}", self.sign(), int),
+            false => format!("{}{}.{}", self.sign(), int, fract),
+        };
+        match PREFIXES.get(((exponent + 24) / 3) as usize) {
+            Some(prefix) if (-24..=24).contains(&exponent) => format!("{}{}", mantissa, prefix),
+            _ => format!("{}e{}", mantissa, exponent),
+        }
+    }
+
+    /// Formats the digits in plain positional notation, e.g. 1230 or 0.0123
+    fn positional(&self) -> String {
+        if self.exponent < 0 {
+            let zeros = "0".repeat((-self.exponent - 1) as usize);
+            return format!("{}0.{}{}", self.sign(), zeros, self.digits);
+        }
+        let (int, fract) = self.split(self.exponent as usize + 1);
+        match fract.is_empty() {
+            true => format!("{}{}", self.sign(), int),
+            false => format!("{}{}.{}", self.sign(), int, fract),
+        }
+    }
+
+    /// Formats the digits in scientific notation, e.g. 1.23e-2
+    fn scientific(&self) -> String {
+        let (int, fract) = self.split(1);
+        match fract.is_empty() {
+            true => format!("{}{}e{}", self.sign(), int, self.exponent),
+            false => format!("{}{}.{}e{}", self.sign(), int, fract, self.exponent),
+        }
+    }
+
+    /// Returns 10 raised to the given power
+    fn pow10(exponent: i64) -> BigRational {
+        let n =
+            BigRational::from_integer(num::pow(BigInt::from(10), exponent.unsigned_abs() as usize));
+        match exponent < 0 {
+            true => BigRational::one() / n,
+            false => n,
+        }
+    }
+
+    /// Returns the sign of the number, i.e. - for negative numbers
+    fn sign(&self) -> &str {
+        if self.negative {
+            "-"
+        } else {
+            ""
+        }
+    }
+
+    /// Splits the digits into an integer part of the given length, padded with
+    /// zeros if necessary, and the remaining fractional part
+    fn split(&self, len: usize) -> (String, String) {
+        match self.digits.len() {
+            n if n <= len => (format!("{:0<width$}", self.digits, width = len), String::new()),
+            _ => (self.digits[..len].to_string(), self.digits[len..].to_string()),
+        }
+    }
+}
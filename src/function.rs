@@ -0,0 +1,88 @@
+use std::fmt;
+use std::rc::Rc;
+
+use crate::expression::Expression;
+
+/// The number of arguments accepted by a function
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Arity {
+    /// The minimum number of arguments
+    pub min: usize,
+    /// The maximum number of arguments, or None if unbounded
+    pub max: Option<usize>,
+}
+
+impl Arity {
+    /// Returns true if the given number of arguments is accepted
+    pub fn accepts(&self, n: usize) -> bool {
+        n >= self.min && self.max.unwrap_or(n) >= n
+    }
+
+    /// Creates an arity accepting exactly n arguments
+    pub fn exact(n: usize) -> Self {
+        Self { min: n, max: Some(n) }
+    }
+
+    /// Creates an arity accepting between min and max arguments, inclusive
+    pub fn range(min: usize, max: usize) -> Self {
+        Self { min, max: Some(max) }
+    }
+
+    /// Creates an arity accepting at least min arguments
+    pub fn variadic(min: usize) -> Self {
+        Self { min, max: None }
+    }
+}
+
+impl From<usize> for Arity {
+    fn from(n: usize) -> Self {
+        Arity::exact(n)
+    }
+}
+
+/// A native function implementation, taking the evaluated arguments
+pub type NativeFn = Rc<dyn Fn(&[f64]) -> f64>;
+
+/// A function which can be called from expressions
+#[derive(Clone)]
+pub enum Function {
+    /// A native Rust function, registered via Environment::register_function()
+    Native { name: String, arity: Arity, func: NativeFn },
+
+    /// A user-defined function, where parameters are given as
+    /// Expression::Parameter in the body
+    User { name: String, params: Vec<String>, body: Expression },
+}
+
+impl fmt::Debug for Function {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Function::Native { name, arity, .. } => {
+                f.debug_struct("Native").field("name", name).field("arity", arity).finish()
+            }
+            Function::User { name, params, body } => f
+                .debug_struct("User")
+                .field("name", name)
+                .field("params", params)
+                .field("body", body)
+                .finish(),
+        }
+    }
+}
+
+impl Function {
+    /// Returns the number of arguments accepted by the function
+    pub fn arity(&self) -> Arity {
+        match self {
+            Function::Native { arity, .. } => *arity,
+            Function::User { params, .. } => params.len().into(),
+        }
+    }
+
+    /// Returns the function name
+    pub fn name(&self) -> &str {
+        match self {
+            Function::Native { name, .. } | Function::User { name, .. } => name,
+        }
+    }
+}
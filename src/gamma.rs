@@ -0,0 +1,75 @@
+use std::f64::consts::{LN_2, PI};
+
+/// The g parameter of the Lanczos approximation
+const LANCZOS_G: f64 = 7.0;
+
+/// The coefficients of the Lanczos approximation for g = 7, giving about 15
+/// significant digits
+const LANCZOS: [f64; 9] = [
+    0.999_999_999_999_809_9,
+    676.520_368_121_885_1,
+    -1_259.139_216_722_402_8,
+    771.323_428_777_653_1,
+    -176.615_029_162_140_6,
+    12.507_343_278_686_905,
+    -0.138_571_095_265_720_12,
+    9.984_369_578_019_572e-6,
+    1.505_632_735_149_311_6e-7,
+];
+
+/// The largest argument with a finite gamma function, as 171! overflows
+const MAX_ARGUMENT: f64 = 171.624_376_956_302_7;
+
+/// Returns the gamma function of a real number, i.e. (x - 1)! for positive
+/// integers, for which it is exact as long as the result is. Returns NaN at
+/// the poles, i.e. zero and negative integers.
+pub fn gamma(x: f64) -> f64 {
+    match x {
+        x if x.is_nan() || x == f64::NEG_INFINITY => f64::NAN,
+        x if x > MAX_ARGUMENT => f64::INFINITY,
+        x if x.fract() == 0.0 && x <= 0.0 => f64::NAN,
+        x if x.fract() == 0.0 => (2..x as u64).fold(1.0, |a, b| a * b as f64),
+        // Reflection formula, as the approximation only holds for x >= 1/2
+        x if x < 0.5 => PI / ((PI * x).sin() * gamma(1.0 - x)),
+        x => {
+            let (t, sum) = lanczos(x);
+            // Splits the power in two to avoid overflow before multiplying
+            let power = t.powf((x - 0.5) / 2.0);
+            (2.0 * PI).sqrt() * power * (-t).exp() * sum * power
+        }
+    }
+}
+
+/// Returns the natural logarithm of the absolute value of the gamma function
+/// of a real number, which unlike the gamma function itself doesn't overflow
+/// for large arguments. Returns infinity at the poles.
+pub fn lgamma(x: f64) -> f64 {
+    match x {
+        x if x.is_infinite() => f64::INFINITY,
+        x if x.fract() == 0.0 && x <= 0.0 => f64::INFINITY,
+        x if x < 0.5 => (PI / (PI * x).sin().abs()).ln() - lgamma(1.0 - x),
+        // More precise where the gamma function doesn't overflow, e.g. at integers
+        x if x <= MAX_ARGUMENT => gamma(x).ln(),
+        x => {
+            let (t, sum) = lanczos(x);
+            (2.0 * PI).sqrt().ln() + (x - 0.5) * t.ln() - t + sum.ln()
+        }
+    }
+}
+
+/// Returns the number of bits in the factorial of a non-negative integer, i.e.
+/// log2(n!), without computing it
+pub fn factorial_bits(n: f64) -> f64 {
+    lgamma(n + 1.0) / LN_2
+}
+
+/// Evaluates the Lanczos approximation at x >= 1/2, giving the base of the
+/// power term and the series sum
+fn lanczos(x: f64) -> (f64, f64) {
+    let z = x - 1.0;
+    let sum = LANCZOS[1..]
+        .iter()
+        .enumerate()
+        .fold(LANCZOS[0], |sum, (i, c)| sum + c / (z + i as f64 + 1.0));
+    (z + LANCZOS_G + 0.5, sum)
+}
@@ -0,0 +1,253 @@
+use std::cmp::Ordering;
+
+use num::bigint::BigInt;
+use num::{Integer, One, Signed, ToPrimitive, Zero};
+
+use crate::gamma;
+use crate::value::{Value, MAX_BITS};
+
+/// The magnitude of floating point integers, i.e. 2^53, from which they may
+/// have been rounded from a different integer
+pub const MAX_EXACT: f64 = 9007199254740992.0;
+
+/// The maximum number of bits of a factorized integer, beyond which each step
+/// of Pollard's rho algorithm takes too long
+const MAX_FACTOR_BITS: usize = 256;
+
+/// The maximum number of bits of an integer tested for primality, beyond
+/// which the Miller-Rabin test takes too long
+pub const MAX_PRIME_BITS: usize = 1024;
+
+/// The maximum number of iterations of Pollard's rho algorithm to factorize
+/// an integer, which finds prime factors up to about the square of it
+const MAX_RHO_ITERATIONS: u32 = 1 << 16;
+
+/// The number of iterations of Pollard's rho algorithm per greatest common
+/// divisor, which is the most expensive step
+const RHO_BATCH: u32 = 32;
+
+/// The primes below 100, used for trial division before the Miller-Rabin
+/// test, of which the first 12 are its bases
+const SMALL_PRIMES: [u32; 25] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+];
+
+/// Returns the binomial coefficient of non-negative integers, i.e. the number
+/// of ways to choose k out of n items, or infinity if too large to represent
+pub fn binomial(n: &BigInt, k: &BigInt) -> Value {
+    if k > n {
+        return Value::Integer(BigInt::zero());
+    }
+    let k = if k * 2 > *n { n - k } else { k.clone() };
+    let (nf, kf) = (n.to_f64().unwrap_or(f64::INFINITY), k.to_f64().unwrap_or(f64::INFINITY));
+    // The result is at most n^k / k!, estimated directly since the factorials
+    // of huge n cancel out in floating point
+    let bits = kf * nf.max(1.0).log2() - gamma::factorial_bits(kf);
+    if bits.is_nan() || bits > MAX_BITS as f64 {
+        return Value::Float(f64::INFINITY);
+    }
+    // Each partial product is itself a binomial coefficient, so divides exactly
+    let mut result = BigInt::one();
+    let mut i = BigInt::one();
+    while i <= k {
+        result = result * (n - &k + &i) / &i;
+        i += 1;
+    }
+    Value::Integer(result)
+}
+
+/// Returns the double factorial of a non-negative integer, i.e. the product of
+/// the integers from 1 to n with the same parity, or infinity if too large to
+/// represent
+pub fn double_factorial(n: &BigInt) -> Value {
+    // n!! is at most sqrt((n + 1)!)
+    let bits = n.to_f64().map_or(f64::INFINITY, |n| gamma::factorial_bits(n + 1.0) / 2.0);
+    if bits.is_nan() || bits > MAX_BITS as f64 {
+        return Value::Float(f64::INFINITY);
+    }
+    let mut result = BigInt::one();
+    let mut i = n.clone();
+    while i > BigInt::one() {
+        result *= &i;
+        i -= 2;
+    }
+    Value::Integer(result)
+}
+
+/// Returns the prime factorization of the absolute value of an integer, as
+/// primes in ascending order with their exponents. Zero and one have none.
+/// Returns None if the integer is too large, or has prime factors too large
+/// to find in reasonable time.
+pub fn factorize(n: &BigInt) -> Option<Vec<(BigInt, u32)>> {
+    let mut n = n.abs();
+    let mut primes = Vec::new();
+    if n.is_zero() {
+        return Some(Vec::new());
+    }
+    if n.bits() > MAX_FACTOR_BITS {
+        return None;
+    }
+    for p in SMALL_PRIMES.iter().map(|&p| BigInt::from(p)) {
+        while n.is_multiple_of(&p) {
+            n /= &p;
+            primes.push(p.clone());
+        }
+    }
+    let mut iterations = MAX_RHO_ITERATIONS;
+    split(n, &mut primes, &mut iterations)?;
+    primes.sort();
+    let mut factors: Vec<(BigInt, u32)> = Vec::new();
+    for p in primes {
+        match factors.last_mut() {
+            Some((q, e)) if *q == p => *e += 1,
+            _ => factors.push((p, 1)),
+        }
+    }
+    Some(factors)
+}
+
+/// Returns true if the integer is prime. Uses the Miller-Rabin test with the
+/// first 12 primes as bases, which is deterministic below 3.3 * 10^24 and
+/// vanishingly unlikely to be wrong above.
+pub fn is_prime(n: &BigInt) -> bool {
+    if n < &BigInt::from(2) {
+        return false;
+    }
+    for p in SMALL_PRIMES.iter().map(|&p| BigInt::from(p)) {
+        match n.cmp(&p) {
+            Ordering::Equal => return true,
+            _ if n.is_multiple_of(&p) => return false,
+            _ => {}
+        }
+    }
+    // Writes n - 1 as d * 2^s with d odd
+    let minus_one: BigInt = n - 1;
+    let mut d = minus_one.clone();
+    let mut s = 0;
+    while d.is_even() {
+        d >>= 1;
+        s += 1;
+    }
+    SMALL_PRIMES[..12].iter().all(|&a| {
+        let mut x = BigInt::from(a).modpow(&d, n);
+        if x.is_one() || x == minus_one {
+            return true;
+        }
+        for _ in 1..s {
+            x = &x * &x % n;
+            if x == minus_one {
+                return true;
+            }
+        }
+        false
+    })
+}
+
+/// Returns the inverse of an integer modulo a positive modulus, i.e. x such
+/// that a * x = 1 (mod m), or None if they aren't coprime
+pub fn mod_inverse(a: &BigInt, m: &BigInt) -> Option<BigInt> {
+    let gcd = a.mod_floor(m).extended_gcd(m);
+    match gcd.gcd.is_one() {
+        true => Some(gcd.x.mod_floor(m)),
+        false => None,
+    }
+}
+
+/// Returns the smallest prime greater than the integer, or None if the
+/// candidates reach the size limit of primality tests before finding one
+pub fn next_prime(n: &BigInt) -> Option<BigInt> {
+    let mut candidate = (n + 1u32).max(BigInt::from(2));
+    while candidate.bits() <= MAX_PRIME_BITS {
+        if is_prime(&candidate) {
+            return Some(candidate);
+        }
+        candidate += 1;
+    }
+    None
+}
+
+/// Returns the number of permutations of non-negative integers, i.e. the
+/// number of ways to arrange k out of n items, or infinity if too large to
+/// represent
+pub fn permutations(n: &BigInt, k: &BigInt) -> Value {
+    if k > n {
+        return Value::Integer(BigInt::zero());
+    }
+    let (nf, kf) = (n.to_f64().unwrap_or(f64::INFINITY), k.to_f64().unwrap_or(f64::INFINITY));
+    // The result is at most n^k, estimated directly since the factorials of
+    // huge n cancel out in floating point
+    let bits = kf * nf.max(1.0).log2();
+    if bits.is_nan() || bits > MAX_BITS as f64 {
+        return Value::Float(f64::INFINITY);
+    }
+    let mut result = BigInt::one();
+    let mut i = n - k + 1;
+    while &i <= n {
+        result *= &i;
+        i += 1;
+    }
+    Value::Integer(result)
+}
+
+/// Returns Euler's totient of a positive integer, i.e. the number of integers
+/// from 1 to n that are coprime to it, or None if it can't be factorized
+pub fn totient(n: &BigInt) -> Option<BigInt> {
+    let factors = factorize(n)?;
+    Some(factors.iter().fold(n.clone(), |result, (p, _)| result / p * (p - 1)))
+}
+
+/// Finds a nontrivial divisor of a composite integer with Pollard's rho
+/// algorithm, trying further polynomials x^2 + c if one fails, or returns None
+/// once the remaining iterations run out
+fn rho(n: &BigInt, iterations: &mut u32) -> Option<BigInt> {
+    let mut c = BigInt::one();
+    loop {
+        let f = |x: &BigInt| (x * x + &c) % n;
+        let (mut x, mut y, mut d) = (BigInt::from(2), BigInt::from(2), BigInt::one());
+        while d.is_one() {
+            // Takes the gcd of the product of a batch of differences
+            let (start_x, start_y) = (x.clone(), y.clone());
+            let mut product = BigInt::one();
+            for _ in 0..RHO_BATCH {
+                *iterations = iterations.checked_sub(1)?;
+                x = f(&x);
+                y = f(&f(&y));
+                product = product * (&x - &y).abs() % n;
+            }
+            d = product.gcd(n);
+            // Retraces the batch one difference at a time, in case it found
+            // several factors at once
+            if &d == n {
+                x = start_x;
+                y = start_y;
+                loop {
+                    x = f(&x);
+                    y = f(&f(&y));
+                    d = (&x - &y).abs().gcd(n);
+                    if !d.is_one() {
+                        break;
+                    }
+                }
+            }
+        }
+        if &d != n {
+            return Some(d);
+        }
+        c += 1;
+    }
+}
+
+/// Splits an integer without small prime factors into its prime factors,
+/// within the remaining iterations of Pollard's rho algorithm
+fn split(n: BigInt, primes: &mut Vec<BigInt>, iterations: &mut u32) -> Option<()> {
+    if n.is_one() {
+        return Some(());
+    }
+    if is_prime(&n) {
+        primes.push(n);
+        return Some(());
+    }
+    let d = rho(&n, iterations)?;
+    split(&n / &d, primes, iterations)?;
+    split(d, primes, iterations)
+}
@@ -7,9 +7,9 @@ use crate::error::Error;
 // A lexer token
 #[derive(Clone, Debug, PartialEq)]
 pub enum Token {
-    /// A literal number
+    /// A literal number, which may be imaginary
     Number(String),
-    /// A name, of a constant or function
+    /// A name, of a constant, variable, or function
     Ident(String),
     /// The addition symbol +
     Plus,
@@ -27,12 +27,44 @@ pub enum Token {
     Percent,
     /// The factorial symbol !
     Exclamation,
+    /// The double factorial symbol !!
+    DoubleExclamation,
+    /// The degree symbol °
+    Degree,
+    /// The bitwise and symbol &
+    Ampersand,
+    /// The bitwise or symbol |
+    Pipe,
+    /// The bitwise not symbol ~
+    Tilde,
+    /// The left shift symbol <<
+    ShiftLeft,
+    /// The right shift symbol >>
+    ShiftRight,
+    /// The less than symbol <
+    Less,
+    /// The less than or equal symbol <=
+    LessEqual,
+    /// The greater than symbol >
+    Greater,
+    /// The greater than or equal symbol >=
+    GreaterEqual,
+    /// The equality symbol ==
+    EqualEqual,
+    /// The inequality symbol !=
+    NotEqual,
     /// An opening parenthesis
     OpenParen,
     /// A closing parenthesis
     CloseParen,
+    /// An opening bracket
+    OpenBracket,
+    /// A closing bracket
+    CloseBracket,
     /// An expression separator ,
     Comma,
+    /// The assignment symbol =
+    Equals,
 }
 
 impl fmt::Display for Token {
@@ -48,24 +80,75 @@ impl fmt::Display for Token {
             Token::SquareRoot => "√",
             Token::Percent => "%",
             Token::Exclamation => "!",
+            Token::DoubleExclamation => "!!",
+            Token::Degree => "°",
+            Token::Ampersand => "&",
+            Token::Pipe => "|",
+            Token::Tilde => "~",
+            Token::ShiftLeft => "<<",
+            Token::ShiftRight => ">>",
+            Token::Less => "<",
+            Token::LessEqual => "<=",
+            Token::Greater => ">",
+            Token::GreaterEqual => ">=",
+            Token::EqualEqual => "==",
+            Token::NotEqual => "!=",
             Token::OpenParen => "(",
             Token::CloseParen => ")",
+            Token::OpenBracket => "[",
+            Token::CloseBracket => "]",
             Token::Comma => ",",
+            Token::Equals => "=",
         })
     }
 }
 
-/// A lexer tokenizes an input string as an iterator
+/// Splits a number literal with a radix prefix into its radix and digits,
+/// e.g. 0xFF into 16 and FF
+pub(crate) fn split_radix(literal: &str) -> Option<(u32, &str)> {
+    let radix = match literal.get(..2)? {
+        "0b" | "0B" => 2,
+        "0o" | "0O" => 8,
+        "0x" | "0X" => 16,
+        _ => return None,
+    };
+    Some((radix, &literal[2..]))
+}
+
+/// A span of input characters, from start (inclusive) to end (exclusive)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    /// Creates a new span
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    /// Creates a span covering both this and the other span
+    pub fn join(self, other: Span) -> Self {
+        Self { start: self.start.min(other.start), end: self.end.max(other.end) }
+    }
+}
+
+/// A lexer tokenizes an input string as an iterator, yielding each token
+/// along with its span in the input
+#[derive(Clone)]
 pub struct Lexer<'a> {
     iter: Peekable<Chars<'a>>,
+    pos: usize,
 }
 
 impl<'a> Iterator for Lexer<'a> {
-    type Item = Result<Token, Error>;
+    type Item = Result<(Token, Span), Error>;
 
-    fn next(&mut self) -> Option<Result<Token, Error>> {
+    fn next(&mut self) -> Option<Result<(Token, Span), Error>> {
         self.scan().map(Ok).or_else(|| {
-            self.iter.peek().map(|&c| Err(Error::Parse(format!("Unexpected character {}", c))))
+            let &found = self.iter.peek()?;
+            Some(Err(Error::UnexpectedCharacter { found, span: Span::new(self.pos, self.pos + 1) }))
         })
     }
 }
@@ -73,7 +156,7 @@ impl<'a> Iterator for Lexer<'a> {
 impl<'a> Lexer<'a> {
     /// Creates a new lexer for the given input string
     pub fn new(input: &'a str) -> Lexer<'a> {
-        Lexer { iter: input.chars().peekable() }
+        Lexer { iter: input.chars().peekable(), pos: 0 }
     }
 
     /// Consumes any whitespace characters
@@ -84,16 +167,44 @@ impl<'a> Lexer<'a> {
     /// Grabs the next character if it matches the predicate function
     fn next_if<F: Fn(char) -> bool>(&mut self, predicate: F) -> Option<char> {
         self.iter.peek().filter(|&c| predicate(*c))?;
+        self.pos += 1;
         self.iter.next()
     }
 
+    /// Grabs the next two-character token if the tokenizer function returns one
+    fn next_if_pair<F: Fn(char, char) -> Option<Token>>(&mut self, tokenizer: F) -> Option<Token> {
+        let mut ahead = self.iter.clone();
+        let token = tokenizer(ahead.next()?, ahead.next()?)?;
+        self.pos += 2;
+        self.iter = ahead;
+        Some(token)
+    }
+
     /// Grabs the next single-character token if the tokenizer function returns one
     fn next_if_token<F: Fn(char) -> Option<Token>>(&mut self, tokenizer: F) -> Option<Token> {
         let token = self.iter.peek().and_then(|&c| tokenizer(c))?;
+        self.pos += 1;
         self.iter.next();
         Some(token)
     }
 
+    /// Grabs the next decimal digits, which may be separated by single
+    /// underscores, e.g. 1_000
+    fn next_digits(&mut self) -> Option<String> {
+        let mut digits = String::new();
+        loop {
+            let mut ahead = self.iter.clone();
+            match ahead.next() {
+                Some(c) if c.is_ascii_digit() => {}
+                Some('_')
+                    if !digits.is_empty() && ahead.next().is_some_and(|c| c.is_ascii_digit()) => {}
+                _ => break,
+            }
+            digits.extend(self.next_if(|_| true));
+        }
+        Some(digits).filter(|d| !d.is_empty())
+    }
+
     /// Grabs the next characters that match the predicate, as a string
     fn next_while<F: Fn(char) -> bool>(&mut self, predicate: F) -> Option<String> {
         let mut value = String::new();
@@ -103,13 +214,17 @@ impl<'a> Lexer<'a> {
         Some(value).filter(|v| !v.is_empty())
     }
 
-    /// Scans the input for the next token if any, ignoring leading whitespace
-    fn scan(&mut self) -> Option<Token> {
+    /// Scans the input for the next token and its span if any, ignoring
+    /// leading whitespace
+    fn scan(&mut self) -> Option<(Token, Span)> {
         self.consume_whitespace();
-        None.or_else(|| self.scan_ident())
+        let start = self.pos;
+        let token = None
+            .or_else(|| self.scan_ident())
             .or_else(|| self.scan_number())
             .or_else(|| self.scan_operator())
-            .or_else(|| self.scan_punctuation())
+            .or_else(|| self.scan_punctuation())?;
+        Some((token, Span::new(start, self.pos)))
     }
 
     /// Scans the input for the next ident token, if any
@@ -123,37 +238,63 @@ impl<'a> Lexer<'a> {
 
     /// Scans the input for the next number token, if any
     fn scan_number(&mut self) -> Option<Token> {
-        let mut num = self.next_while(|c| c.is_digit(10))?;
+        let mut num = self.next_digits()?;
+        // A radix prefix, followed by alphanumeric digits which are checked by
+        // the parser, e.g. 0xFF
+        if num == "0" && self.iter.peek().is_some_and(|c| "bBoOxX".contains(*c)) {
+            num.extend(self.next_while(|c| c.is_alphanumeric() || c == '_'));
+            return Some(Token::Number(num));
+        }
         if let Some(sep) = self.next_if(|c| c == '.') {
             num.push(sep);
-            while let Some(dec) = self.next_if(|c| c.is_digit(10)) {
-                num.push(dec)
-            }
+            num.extend(self.next_digits());
         }
         if let Some(exp) = self.next_if(|c| c == 'e' || c == 'E') {
             num.push(exp);
             if let Some(sign) = self.next_if(|c| c == '+' || c == '-') {
                 num.push(sign)
             }
-            while let Some(c) = self.next_if(|c| c.is_digit(10)) {
-                num.push(c)
-            }
+            num.extend(self.next_digits());
+        }
+        // An imaginary suffix, unless it begins an ident
+        let mut ahead = self.iter.clone();
+        if ahead.next() == Some('i') && !ahead.next().is_some_and(|c| c.is_alphanumeric()) {
+            num.extend(self.next_if(|c| c == 'i'));
         }
         Some(Token::Number(num))
     }
 
     /// Scans the input for the next operator token, if any
     fn scan_operator(&mut self) -> Option<Token> {
-        self.next_if_token(|c| match c {
-            '+' => Some(Token::Plus),
-            '-' => Some(Token::Minus),
-            '*' => Some(Token::Asterisk),
-            '/' => Some(Token::Slash),
-            '^' => Some(Token::Caret),
-            '√' => Some(Token::SquareRoot),
-            '%' => Some(Token::Percent),
-            '!' => Some(Token::Exclamation),
+        let pair = self.next_if_pair(|a, b| match (a, b) {
+            ('<', '<') => Some(Token::ShiftLeft),
+            ('>', '>') => Some(Token::ShiftRight),
+            ('<', '=') => Some(Token::LessEqual),
+            ('>', '=') => Some(Token::GreaterEqual),
+            ('=', '=') => Some(Token::EqualEqual),
+            // Takes precedence over factorial, e.g. n != 1 rather than n! = 1
+            ('!', '=') => Some(Token::NotEqual),
+            ('!', '!') => Some(Token::DoubleExclamation),
             _ => None,
+        });
+        pair.or_else(|| {
+            self.next_if_token(|c| match c {
+                '+' => Some(Token::Plus),
+                '-' => Some(Token::Minus),
+                '*' => Some(Token::Asterisk),
+                '/' => Some(Token::Slash),
+                '^' => Some(Token::Caret),
+                '√' => Some(Token::SquareRoot),
+                '%' => Some(Token::Percent),
+                '!' => Some(Token::Exclamation),
+                '°' => Some(Token::Degree),
+                '&' => Some(Token::Ampersand),
+                '|' => Some(Token::Pipe),
+                '~' => Some(Token::Tilde),
+                '<' => Some(Token::Less),
+                '>' => Some(Token::Greater),
+                _ => None,
+            })
         })
     }
 
@@ -162,7 +303,10 @@ impl<'a> Lexer<'a> {
         self.next_if_token(|c| match c {
             '(' => Some(Token::OpenParen),
             ')' => Some(Token::CloseParen),
+            '[' => Some(Token::OpenBracket),
+            ']' => Some(Token::CloseBracket),
             ',' => Some(Token::Comma),
+            '=' => Some(Token::Equals),
             _ => None,
         })
     }
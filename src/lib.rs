@@ -1,7 +1,19 @@
 #![warn(clippy::all)]
 
+pub mod angle;
 pub mod cli;
+pub mod decimal;
+pub mod environment;
 pub mod error;
 pub mod expression;
+pub mod format;
+pub mod function;
+pub mod gamma;
+pub mod integer;
 pub mod lexer;
+pub mod matrix;
 pub mod parser;
+pub mod statement;
+pub mod statistics;
+pub mod unit;
+pub mod value;
@@ -0,0 +1,277 @@
+use std::cmp::Ordering;
+
+use num::bigint::BigInt;
+use num::{Integer, ToPrimitive, Zero};
+
+use crate::value::{Value, MAX_BITS};
+
+/// The maximum number of elements of a constructed matrix, e.g. by zeros()
+pub const MAX_ELEMENTS: usize = 1 << 20;
+
+/// A matrix, as rows of the same length
+pub type Matrix = Vec<Vec<Value>>;
+
+/// Returns the determinant of a square matrix
+pub fn determinant(m: &[Vec<Value>]) -> Value {
+    let mut m = m.to_vec();
+    let n = m.len();
+    let (pivots, swaps) = eliminate(&mut m, n);
+    if pivots < n {
+        return Value::from(0);
+    }
+    let product = (1..n).fold(m[0][0].clone(), |product, i| &product * &m[i][i]);
+    match swaps % 2 {
+        0 => product,
+        _ => -&product,
+    }
+}
+
+/// Returns the eigenvalues of a real symmetric matrix in ascending order,
+/// using the cyclic Jacobi method
+pub fn eigenvalues(m: &[Vec<Value>]) -> Vec<f64> {
+    let n = m.len();
+    let mut a: Vec<Vec<f64>> =
+        m.iter().map(|row| row.iter().map(Value::to_f64).collect()).collect();
+    // Each sweep rotates away every off-diagonal element, which converges
+    // quadratically, so that few sweeps are needed
+    for _ in 0..64 {
+        if (0..n).all(|p| (p + 1..n).all(|q| a[p][q] == 0.0)) {
+            break;
+        }
+        for p in 0..n {
+            for q in p + 1..n {
+                if a[p][q] == 0.0 {
+                    continue;
+                }
+                // Chooses the smaller rotation angle, with t = tan(angle)
+                let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+                let t = match theta {
+                    theta if theta.is_infinite() => 0.0,
+                    theta => theta.signum() / (theta.abs() + theta.hypot(1.0)),
+                };
+                let c = 1.0 / t.hypot(1.0);
+                let s = t * c;
+                // Updates the diagonal by the rotated element, which is more
+                // accurate than rotating it, and keeps the matrix symmetric
+                for k in (0..n).filter(|&k| k != p && k != q) {
+                    let (x, y) = (a[k][p], a[k][q]);
+                    a[k][p] = c * x - s * y;
+                    a[k][q] = s * x + c * y;
+                    a[p][k] = a[k][p];
+                    a[q][k] = a[k][q];
+                }
+                a[p][p] -= t * a[p][q];
+                a[q][q] += t * a[p][q];
+                a[p][q] = 0.0;
+                a[q][p] = 0.0;
+            }
+        }
+    }
+    let mut values: Vec<f64> = (0..n).map(|i| a[i][i]).collect();
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    values
+}
+
+/// Returns the identity matrix with the given number of rows and columns
+pub fn identity(n: usize) -> Matrix {
+    (0..n).map(|i| (0..n).map(|j| Value::from(i == j)).collect()).collect()
+}
+
+/// Returns the inverse of a square matrix, or None if it is singular
+pub fn inverse(m: &[Vec<Value>]) -> Option<Matrix> {
+    solve(m, &identity(m.len()))
+}
+
+/// Returns true if a square matrix is symmetric, within rounding error for
+/// floating point elements
+pub fn is_symmetric(m: &[Vec<Value>]) -> bool {
+    let scale = scale(m);
+    (0..m.len()).all(|i| (0..i).all(|j| negligible(&(&m[i][j] - &m[j][i]), scale, m.len())))
+}
+
+/// Raises a square matrix to a non-negative integer power by repeated squaring.
+/// Exact elements are converted to floating point numbers if the result would
+/// be too large, as for powers of numbers.
+pub fn power(m: &[Vec<Value>], e: &BigInt) -> Matrix {
+    // Each product adds about the size of the elements, and of the sum over
+    // a row, to the size of the result
+    let size = m.iter().flatten().filter_map(Value::to_ratio);
+    let bits = size.map(|n| n.numer().bits() + n.denom().bits()).max().unwrap_or(0);
+    let bits = (bits + m.len().next_power_of_two().trailing_zeros() as usize) as f64;
+    let inexact = e.to_f64().unwrap_or(f64::INFINITY) * bits > MAX_BITS as f64;
+    let float = |n: &Value| match n.to_ratio() {
+        Some(_) if inexact => Value::Float(n.to_f64()),
+        _ => n.clone(),
+    };
+    let mut base: Matrix = m.iter().map(|row| row.iter().map(float).collect()).collect();
+    let (mut result, mut e) = (identity(m.len()), e.clone());
+    while !e.is_zero() {
+        if e.is_odd() {
+            result = multiply(&result, &base);
+        }
+        e >>= 1;
+        if !e.is_zero() {
+            base = multiply(&base, &base);
+        }
+    }
+    result
+}
+
+/// Multiplies values as matrices if either is a matrix, and the other is a
+/// matrix or a vector, which is a row on the LHS or a column on the RHS.
+/// Returns None for other values, or the mismatched number of columns of the
+/// LHS and rows of the RHS.
+pub fn product(l: &Value, r: &Value) -> Option<Result<Value, (usize, usize)>> {
+    let (lhs, rhs) = (rows(l), rows(r));
+    if lhs.is_none() && rhs.is_none() {
+        return None;
+    }
+    // A vector operand gives a vector, rather than a matrix of one row or column
+    let flatten = lhs.is_none() || rhs.is_none();
+    let lhs = lhs.or_else(|| vector(l).map(|n| vec![n]))?;
+    let rhs = rhs.or_else(|| vector(r).map(|n| n.into_iter().map(|n| vec![n]).collect()))?;
+    if lhs[0].len() != rhs.len() {
+        return Some(Err((lhs[0].len(), rhs.len())));
+    }
+    let product = multiply(&lhs, &rhs);
+    Some(Ok(match flatten {
+        true => Value::List(product.into_iter().flatten().collect()),
+        false => to_value(product),
+    }))
+}
+
+/// Returns the rank of a matrix, i.e. the number of linearly independent rows
+pub fn rank(m: &[Vec<Value>]) -> usize {
+    let columns = m[0].len();
+    eliminate(&mut m.to_vec(), columns).0
+}
+
+/// Returns the rows of a matrix, i.e. a non-empty list of non-empty vectors of
+/// the same length, or None for other values
+pub fn rows(value: &Value) -> Option<Matrix> {
+    let rows = match value {
+        Value::List(rows) if !rows.is_empty() => rows,
+        _ => return None,
+    };
+    let rows = rows
+        .iter()
+        .map(|row| vector(row).filter(|row| !row.is_empty()))
+        .collect::<Option<Matrix>>()?;
+    match rows.iter().all(|row| row.len() == rows[0].len()) {
+        true => Some(rows),
+        false => None,
+    }
+}
+
+/// Solves the linear equations m * x = b for x, where m is a square matrix
+/// and b has as many rows, or returns None if m is singular
+pub fn solve(m: &[Vec<Value>], b: &[Vec<Value>]) -> Option<Matrix> {
+    let n = m.len();
+    // Reduces the augmented matrix [m | b] to [u | c], where u is upper
+    // triangular, and solves u * x = c by back substitution from the last row
+    let mut augmented: Matrix =
+        m.iter().zip(b).map(|(m, b)| m.iter().chain(b).cloned().collect()).collect();
+    if eliminate(&mut augmented, n).0 < n {
+        return None;
+    }
+    let mut x: Matrix = Vec::with_capacity(n);
+    for (i, row) in augmented.iter().enumerate().rev() {
+        let solution = (n..row.len())
+            .map(|j| {
+                let known = x.iter().rev().zip(&row[i + 1..n]).map(|(x, u)| u * &x[j - n]);
+                known.fold(row[j].clone(), |c, n| &c - &n).divide(&row[i])
+            })
+            .collect();
+        x.push(solution);
+    }
+    x.reverse();
+    Some(x)
+}
+
+/// Converts a matrix to a list of its rows
+pub fn to_value(m: Matrix) -> Value {
+    Value::List(m.into_iter().map(Value::List).collect())
+}
+
+/// Returns the transpose of a matrix, i.e. with rows and columns swapped
+pub fn transpose(m: &[Vec<Value>]) -> Matrix {
+    (0..m[0].len()).map(|j| m.iter().map(|row| row[j].clone()).collect()).collect()
+}
+
+/// Returns the elements of a vector, i.e. a list whose elements aren't
+/// lists, or None for other values
+pub fn vector(value: &Value) -> Option<Vec<Value>> {
+    match value {
+        Value::List(n) if !n.iter().any(|n| matches!(n, Value::List(_))) => Some(n.clone()),
+        _ => None,
+    }
+}
+
+/// Returns a matrix of zeros with the given number of rows and columns
+pub fn zeros(rows: usize, columns: usize) -> Matrix {
+    vec![vec![Value::from(0); columns]; rows]
+}
+
+/// Reduces the given number of leading columns of a matrix to row echelon
+/// form by Gaussian elimination, choosing the largest pivot in each column.
+/// Returns the number of pivots, which are on the leading diagonal if there is
+/// one per column, and the number of row swaps.
+fn eliminate(m: &mut Matrix, columns: usize) -> (usize, usize) {
+    let (scale, size) = (scale(m), m.len().max(columns));
+    let (mut pivots, mut swaps) = (0, 0);
+    for column in 0..columns {
+        let row = pivots;
+        let magnitude = |i: &usize| m[*i][column].abs().to_f64();
+        let pivot = (row..m.len())
+            .filter(|&i| !negligible(&m[i][column], scale, size))
+            .max_by(|a, b| magnitude(a).partial_cmp(&magnitude(b)).unwrap_or(Ordering::Equal));
+        let pivot = match pivot {
+            Some(pivot) => pivot,
+            None => continue,
+        };
+        if pivot != row {
+            m.swap(pivot, row);
+            swaps += 1;
+        }
+        let pivot_row = m[row].clone();
+        for target in m[row + 1..].iter_mut() {
+            let factor = target[column].divide(&pivot_row[column]);
+            for (n, p) in target.iter_mut().zip(&pivot_row) {
+                *n = &*n - &(&factor * p);
+            }
+        }
+        pivots += 1;
+    }
+    (pivots, swaps)
+}
+
+/// Returns the product of two matrices, where the columns of the LHS match
+/// the rows of the RHS
+fn multiply(l: &[Vec<Value>], r: &[Vec<Value>]) -> Matrix {
+    l.iter()
+        .map(|row| {
+            (0..r[0].len())
+                .map(|j| {
+                    let mut products = row.iter().zip(r).map(|(n, r)| n * &r[j]);
+                    let first = products.next().unwrap_or_else(|| Value::from(0));
+                    products.fold(first, |sum, n| &sum + &n)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Returns true if a value is zero, or within rounding error of zero for
+/// floating point values, relative to the largest magnitude of the elements
+/// of a matrix of the given size
+fn negligible(n: &Value, scale: f64, size: usize) -> bool {
+    match n.to_ratio() {
+        Some(_) => n.is_zero(),
+        None => n.abs().to_f64() <= scale * size as f64 * f64::EPSILON,
+    }
+}
+
+/// Returns the largest magnitude of the elements of a matrix
+fn scale(m: &[Vec<Value>]) -> f64 {
+    m.iter().flatten().map(|n| n.abs().to_f64()).fold(0.0, f64::max)
+}
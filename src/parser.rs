@@ -1,12 +1,22 @@
 use std::iter::Peekable;
 
-use crate::error::Error;
+use crate::decimal::Rounding;
+use crate::environment::Environment;
+use crate::error::{Error, Expected};
 use crate::expression::{Constant, Expression};
-use crate::lexer::{Lexer, Token};
+use crate::function::{Arity, Function};
+use crate::lexer::{split_radix, Lexer, Span, Token};
+use crate::statement::Statement;
+use crate::statistics::Aggregate;
+use crate::unit::Unit;
+use crate::value::Value;
 
 const ASSOC_LEFT: u8 = 1;
 const ASSOC_RIGHT: u8 = 0;
 
+/// A name along with its span in the input
+type SpannedName = (String, Span);
+
 /// An operator represents a token that operates on surrounding values
 trait Operator: Sized {
     /// Creates an operator from a token, if appropriate
@@ -21,7 +31,9 @@ trait Operator: Sized {
 
 // Prefix operators
 enum PrefixOperator {
+    BitNot,
     Minus,
+    Not,
     Plus,
     SquareRoot,
 }
@@ -31,7 +43,9 @@ impl PrefixOperator {
     fn build(&self, operand: Expression) -> Expression {
         use PrefixOperator::*;
         match self {
+            BitNot => Expression::BitNot(operand.into()),
             Minus => Expression::Negate(operand.into()),
+            Not => Expression::Not(operand.into()),
             Plus => operand,
             SquareRoot => Expression::SquareRoot(operand.into()),
         }
@@ -42,7 +56,9 @@ impl Operator for PrefixOperator {
     fn from(token: &Token) -> Option<Self> {
         use PrefixOperator::*;
         match token {
+            Token::Tilde => Some(BitNot),
             Token::Minus => Some(Minus),
+            Token::Ident(name) if name == "not" => Some(Not),
             Token::Plus => Some(Plus),
             Token::SquareRoot => Some(SquareRoot),
             _ => None,
@@ -54,30 +70,66 @@ impl Operator for PrefixOperator {
     }
 
     fn prec(&self) -> u8 {
-        5
+        use PrefixOperator::*;
+        match self {
+            Not => 3,
+            BitNot | Minus | Plus | SquareRoot => 13,
+        }
     }
 }
 
 /// Infix operators
 enum InfixOperator {
     Add,
+    And,
+    BitAnd,
+    BitOr,
+    BitXor,
     Divide,
+    Equal,
     Exponentiate,
+    Greater,
+    GreaterEqual,
+    Less,
+    LessEqual,
     Modulo,
     Multiply,
+    NotEqual,
+    Or,
+    ShiftLeft,
+    ShiftRight,
     Subtract,
 }
 
 impl InfixOperator {
+    /// Returns true if the operator is a comparison, which can be chained
+    fn is_comparison(&self) -> bool {
+        use InfixOperator::*;
+        matches!(self, Equal | Greater | GreaterEqual | Less | LessEqual | NotEqual)
+    }
+
     // Builds an expression node for the infix operator
     fn build(&self, lhs: Expression, rhs: Expression) -> Expression {
         use InfixOperator::*;
         match self {
             Add => Expression::Add { lhs: lhs.into(), rhs: rhs.into() },
+            And => Expression::And { lhs: lhs.into(), rhs: rhs.into() },
+            BitAnd => Expression::BitAnd { lhs: lhs.into(), rhs: rhs.into() },
+            BitOr => Expression::BitOr { lhs: lhs.into(), rhs: rhs.into() },
+            BitXor => Expression::BitXor { lhs: lhs.into(), rhs: rhs.into() },
             Divide => Expression::Divide { lhs: lhs.into(), rhs: rhs.into() },
+            Equal => Expression::Equal { lhs: lhs.into(), rhs: rhs.into() },
             Exponentiate => Expression::Exponentiate { lhs: lhs.into(), rhs: rhs.into() },
+            Greater => Expression::Greater { lhs: lhs.into(), rhs: rhs.into() },
+            GreaterEqual => Expression::GreaterEqual { lhs: lhs.into(), rhs: rhs.into() },
+            Less => Expression::Less { lhs: lhs.into(), rhs: rhs.into() },
+            LessEqual => Expression::LessEqual { lhs: lhs.into(), rhs: rhs.into() },
             Modulo => Expression::Modulo { lhs: lhs.into(), rhs: rhs.into() },
             Multiply => Expression::Multiply { lhs: lhs.into(), rhs: rhs.into() },
+            NotEqual => Expression::NotEqual { lhs: lhs.into(), rhs: rhs.into() },
+            Or => Expression::Or { lhs: lhs.into(), rhs: rhs.into() },
+            ShiftLeft => Expression::ShiftLeft { lhs: lhs.into(), rhs: rhs.into() },
+            ShiftRight => Expression::ShiftRight { lhs: lhs.into(), rhs: rhs.into() },
             Subtract => Expression::Subtract { lhs: lhs.into(), rhs: rhs.into() },
         }
     }
@@ -93,6 +145,19 @@ impl Operator for InfixOperator {
             Token::Slash => Some(Divide),
             Token::Percent => Some(Modulo),
             Token::Caret => Some(Exponentiate),
+            Token::Ampersand => Some(BitAnd),
+            Token::Pipe => Some(BitOr),
+            Token::Ident(name) if name == "xor" => Some(BitXor),
+            Token::ShiftLeft => Some(ShiftLeft),
+            Token::ShiftRight => Some(ShiftRight),
+            Token::Less => Some(Less),
+            Token::LessEqual => Some(LessEqual),
+            Token::Greater => Some(Greater),
+            Token::GreaterEqual => Some(GreaterEqual),
+            Token::EqualEqual => Some(Equal),
+            Token::NotEqual => Some(NotEqual),
+            Token::Ident(name) if name == "and" => Some(And),
+            Token::Ident(name) if name == "or" => Some(Or),
             _ => None,
         }
     }
@@ -108,15 +173,24 @@ impl Operator for InfixOperator {
     fn prec(&self) -> u8 {
         use InfixOperator::*;
         match self {
-            Add | Subtract => 1,
-            Multiply | Divide | Modulo => 2,
-            Exponentiate => 3,
+            Or => 1,
+            And => 2,
+            Equal | Greater | GreaterEqual | Less | LessEqual | NotEqual => 4,
+            BitOr => 5,
+            BitXor => 6,
+            BitAnd => 7,
+            ShiftLeft | ShiftRight => 8,
+            Add | Subtract => 9,
+            Multiply | Divide | Modulo => 10,
+            Exponentiate => 11,
         }
     }
 }
 
 /// Postfix operators
 enum PostfixOperator {
+    Degree,
+    DoubleFactorial,
     Factorial,
 }
 
@@ -125,6 +199,8 @@ impl PostfixOperator {
     fn build(&self, operand: Expression) -> Expression {
         use PostfixOperator::*;
         match self {
+            Degree => Expression::DegreeAngle(operand.into()),
+            DoubleFactorial => Expression::DoubleFactorial(operand.into()),
             Factorial => Expression::Factorial(operand.into()),
         }
     }
@@ -134,6 +210,8 @@ impl Operator for PostfixOperator {
     fn from(token: &Token) -> Option<Self> {
         use PostfixOperator::*;
         match token {
+            Token::Degree => Some(Degree),
+            Token::DoubleExclamation => Some(DoubleFactorial),
             Token::Exclamation => Some(Factorial),
             _ => None,
         }
@@ -144,108 +222,419 @@ impl Operator for PostfixOperator {
     }
 
     fn prec(&self) -> u8 {
-        4
+        12
     }
 }
 
 /// Parses an input string into an expression
 pub struct Parser<'a> {
     lexer: Peekable<Lexer<'a>>,
+    end: usize,
+    env: Option<&'a Environment>,
+    params: Vec<String>,
 }
 
 impl<'a> Parser<'a> {
     /// Creates a new parser for the given input string
     pub fn new(input: &str) -> Parser {
-        Parser { lexer: Lexer::new(input).peekable() }
+        Parser {
+            lexer: Lexer::new(input).peekable(),
+            end: input.chars().count(),
+            env: None,
+            params: Vec::new(),
+        }
     }
 
-    /// Builds an expression node from a constant name
-    fn build_constant(&self, name: String) -> Result<Expression, Error> {
-        match name.to_lowercase().as_str() {
-            "e" => Ok(Constant::E.into()),
-            "inf" => Ok(Constant::Infinity.into()),
-            "nan" => Ok(Constant::NaN.into()),
-            "pi" => Ok(Constant::Pi.into()),
-            "π" => Ok(Constant::Pi.into()),
-            _ => Err(Error::Parse(format!("Unknown constant {}", name))),
-        }
-    }
-
-    // Builds an expression node from a function call
-    fn build_function(&self, name: String, mut args: Vec<Expression>) -> Result<Expression, Error> {
-        args.reverse();
-        let mut arg = || {
-            args.pop()
-                .map(|expr| expr.into())
-                .ok_or_else(|| Error::Parse(format!("Missing argument for {}()", name)))
-        };
-        let expr = match name.to_lowercase().as_str() {
-            "cos" => Expression::Cosine(arg()?),
-            "degrees" => Expression::Degrees(arg()?),
-            "radians" => Expression::Radians(arg()?),
-            "round" => {
-                Expression::Round { value: arg()?, decimals: arg().unwrap_or_else(|_| 0.0.into()) }
+    /// Creates a new parser for the given input string, which resolves
+    /// variables and functions defined in the given environment
+    pub fn with_environment(input: &'a str, env: &'a Environment) -> Parser<'a> {
+        Parser { env: Some(env), ..Parser::new(input) }
+    }
+
+    /// Builds an expression node from a constant, parameter, or variable name
+    fn build_constant(&self, name: String, span: Span) -> Result<Expression, Error> {
+        if let Some(constant) = self.lookup_constant(&name) {
+            Ok(constant.into())
+        } else if let Some(i) = self.params.iter().position(|p| *p == name) {
+            Ok(Expression::Parameter(i))
+        } else if self.env.and_then(|env| env.get(&name)).is_some() {
+            Ok(Expression::Variable(name))
+        } else {
+            Err(Error::UnknownConstant { name, span })
+        }
+    }
+
+    /// Builds a function definition statement, parsing the function body
+    fn build_definition(
+        &mut self,
+        (name, span): SpannedName,
+        params: Vec<SpannedName>,
+    ) -> Result<Statement, Error> {
+        let native = matches!(
+            self.env.and_then(|env| env.function(&name)).as_deref(),
+            Some(Function::Native { .. })
+        );
+        if Self::lookup_function(&name).is_some() || native {
+            return Err(Error::RedefineFunction { name, span });
+        }
+        for (param, span) in params {
+            if self.lookup_constant(&param).is_some() {
+                return Err(Error::InvalidParameter { name: param, span });
+            } else if self.params.contains(&param) {
+                return Err(Error::DuplicateParameter { name: param, span });
             }
-            "sin" => Expression::Sine(arg()?),
-            "sqrt" => Expression::SquareRoot(arg()?),
-            "tan" => Expression::Tangent(arg()?),
-            _ => return Err(Error::Parse(format!("Unknown function {}", name))),
+            self.params.push(param);
+        }
+        let body = self.parse_expression(0)?;
+        Ok(Statement::Define(Function::User {
+            name,
+            params: self.params.drain(..).collect(),
+            body,
+        }))
+    }
+
+    // Builds an expression node from a function call, checking the number of
+    // arguments. Rounding functions also take an optional rounding mode.
+    fn build_function(
+        &self,
+        name: String,
+        args: Vec<Expression>,
+        rounding: Option<Rounding>,
+        span: Span,
+    ) -> Result<Expression, Error> {
+        let function = self.env.and_then(|env| env.function(&name));
+        let expected = match function.as_ref().map(|f| f.arity()) {
+            Some(arity) => arity,
+            None => Self::lookup_function(&name).ok_or_else(|| {
+                let span = Span::new(span.start, span.start + name.chars().count());
+                Error::UnknownFunction { name: name.clone(), span }
+            })?,
         };
-        if args.is_empty() {
-            Ok(expr)
-        } else {
-            Err(Error::Parse(format!("Unexpected argument for {}()", name)))
+        if !expected.accepts(args.len()) {
+            return Err(Error::ArityMismatch { name, expected, got: args.len(), span });
+        }
+        if let Some(function) = function {
+            return Ok(Expression::Call { function, args });
+        }
+        if let Some(function) = Aggregate::from_name(&name) {
+            return Ok(Expression::Aggregate { function, args });
+        }
+        // Optional arguments of built-in functions default to 0, except as noted
+        let count = args.len();
+        let mut args = args.into_iter().map(Box::new);
+        let mut arg = || args.next().unwrap_or_else(|| 0.0.into());
+        let rounding = rounding.unwrap_or_default();
+        Ok(match name.to_lowercase().as_str() {
+            "abs" => Expression::Absolute(arg()),
+            "acos" => Expression::ArcCosine(arg()),
+            "acosh" => Expression::InverseHyperbolicCosine(arg()),
+            "arg" => Expression::Argument(arg()),
+            "asin" => Expression::ArcSine(arg()),
+            "asinh" => Expression::InverseHyperbolicSine(arg()),
+            "atan" => Expression::ArcTangent(arg()),
+            "atan2" => Expression::ArcTangent2 { y: arg(), x: arg() },
+            "atanh" => Expression::InverseHyperbolicTangent(arg()),
+            "ceil" => Expression::Ceiling(arg()),
+            "choose" | "ncr" => Expression::Binomial { n: arg(), k: arg() },
+            "conj" => Expression::Conjugate(arg()),
+            "cbrt" => Expression::CubeRoot(arg()),
+            "cos" => Expression::Cosine(arg()),
+            "cosh" => Expression::HyperbolicCosine(arg()),
+            "cot" => Expression::Cotangent(arg()),
+            "cross" => Expression::CrossProduct { lhs: arg(), rhs: arg() },
+            "csc" => Expression::Cosecant(arg()),
+            "degrees" => Expression::Degrees(arg()),
+            "det" => Expression::Determinant(arg()),
+            "dot" => Expression::DotProduct { lhs: arg(), rhs: arg() },
+            "eig" => Expression::Eigenvalues(arg()),
+            "exp" => Expression::Exponential(arg()),
+            "expm1" => Expression::ExponentialMinusOne(arg()),
+            "factor" => Expression::Factorize(arg()),
+            "floor" => Expression::Floor(arg()),
+            "frac" => Expression::FractionalPart(arg()),
+            "gamma" => Expression::Gamma(arg()),
+            "gcd" => Expression::GreatestCommonDivisor { lhs: arg(), rhs: arg() },
+            "hypot" => Expression::Hypotenuse { lhs: arg(), rhs: arg() },
+            "identity" => Expression::Identity(arg()),
+            "if" => Expression::If { condition: arg(), then: arg(), otherwise: arg() },
+            "im" => Expression::Imaginary(arg()),
+            "inv" => Expression::MatrixInverse(arg()),
+            "isprime" => Expression::IsPrime(arg()),
+            "lcm" => Expression::LeastCommonMultiple { lhs: arg(), rhs: arg() },
+            "len" => Expression::Length(arg()),
+            "lgamma" => Expression::LogGamma(arg()),
+            "ln" => Expression::NaturalLogarithm(arg()),
+            "log" => Expression::Logarithm { value: arg(), base: arg() },
+            "log10" => Expression::CommonLogarithm(arg()),
+            "log1p" => Expression::LogarithmOnePlus(arg()),
+            "log2" => Expression::BinaryLogarithm(arg()),
+            "modinv" => Expression::ModularInverse { value: arg(), modulus: arg() },
+            "modpow" => Expression::ModularPower { base: arg(), exponent: arg(), modulus: arg() },
+            "nextprime" => Expression::NextPrime(arg()),
+            "norm" => Expression::Norm(arg()),
+            "npr" => Expression::Permutations { n: arg(), k: arg() },
+            "nthroot" => Expression::Root { value: arg(), degree: arg() },
+            "radians" => Expression::Radians(arg()),
+            "rank" => Expression::Rank(arg()),
+            "re" => Expression::Real(arg()),
+            "round" => Expression::Round { value: arg(), decimals: arg(), rounding },
+            "roundto" => Expression::RoundTo { value: arg(), step: arg(), rounding },
+            "sec" => Expression::Secant(arg()),
+            "sign" => Expression::Sign(arg()),
+            "sin" => Expression::Sine(arg()),
+            "sinh" => Expression::HyperbolicSine(arg()),
+            "solve" => Expression::Solve { matrix: arg(), rhs: arg() },
+            "sqrt" => Expression::SquareRoot(arg()),
+            "tan" => Expression::Tangent(arg()),
+            "tanh" => Expression::HyperbolicTangent(arg()),
+            "totient" => Expression::Totient(arg()),
+            "transpose" => Expression::Transpose(arg()),
+            "trunc" => Expression::Truncate(arg()),
+            "zeros" => {
+                // A single size gives a square matrix
+                let rows = arg();
+                let columns = if count > 1 { arg() } else { rows.clone() };
+                Expression::Zeros { rows, columns }
+            }
+            _ => unreachable!("unknown built-in function {}", name),
+        })
+    }
+
+    /// Builds a number node from a number literal, and the unit following it
+    /// if any
+    fn build_number(&mut self, literal: String, span: Span) -> Result<Expression, Error> {
+        if let Some((radix, digits)) = split_radix(&literal) {
+            let invalid = digits.chars().enumerate().find(|(_, c)| *c != '_' && !c.is_digit(radix));
+            if let Some((i, digit)) = invalid {
+                let start = span.start + 2 + i;
+                return Err(Error::InvalidDigit {
+                    digit,
+                    radix,
+                    span: Span::new(start, start + 1),
+                });
+            }
+        }
+        // Literals are kept exact, for use in exact mode
+        match (Value::parse(&literal), self.parse_unit()) {
+            (Some(value), Some(unit)) => Ok(value.with_unit(unit).into()),
+            (Some(value), None) => Ok(value.into()),
+            (None, _) => Err(Error::InvalidNumber { literal, span }),
         }
     }
 
-    /// Builds a number node from a number literal
-    fn build_number(&self, literal: String) -> Result<Expression, Error> {
-        Ok(literal.parse::<f64>()?.into())
+    /// Returns true if the name is bound to a constant, parameter, or variable
+    fn is_bound(&self, name: &str) -> bool {
+        self.lookup_constant(name).is_some()
+            || self.params.iter().any(|p| p == name)
+            || self.env.and_then(|env| env.get(name)).is_some()
     }
 
-    /// Grabs the next lexer token, or throws an error if none is found.
-    fn next(&mut self) -> Result<Token, Error> {
-        self.lexer.next().unwrap_or_else(|| Err(Error::Parse("Unexpected end of input".into())))
+    /// Looks up a built-in or registered constant by name, if it exists
+    fn lookup_constant(&self, name: &str) -> Option<Constant> {
+        match name.to_lowercase().as_str() {
+            "e" => Some(Constant::E),
+            "i" => Some(Constant::I),
+            "inf" => Some(Constant::Infinity),
+            "nan" => Some(Constant::NaN),
+            "pi" => Some(Constant::Pi),
+            "π" => Some(Constant::Pi),
+            _ => self
+                .env
+                .and_then(|env| env.constant(name))
+                .map(|value| Constant::Custom(name.to_string(), value)),
+        }
+    }
+
+    /// Looks up the arity of a built-in function by name, if it exists
+    fn lookup_function(name: &str) -> Option<Arity> {
+        match name.to_lowercase().as_str() {
+            "abs" | "acos" | "acosh" | "arg" | "asin" | "asinh" | "atan" | "atanh" | "cbrt"
+            | "ceil" | "conj" | "cos" | "cosh" | "cot" | "csc" | "degrees" | "det" | "eig"
+            | "exp" | "expm1" | "factor" | "floor" | "frac" | "gamma" | "identity" | "im"
+            | "inv" | "isprime" | "len" | "lgamma" | "ln" | "log10" | "log1p" | "log2"
+            | "nextprime" | "norm" | "radians" | "rank" | "re" | "sec" | "sign" | "sin"
+            | "sinh" | "sqrt" | "tan" | "tanh" | "totient" | "transpose" | "trunc" => {
+                Some(Arity::exact(1))
+            }
+            "atan2" | "choose" | "cross" | "dot" | "gcd" | "hypot" | "lcm" | "log" | "modinv"
+            | "ncr" | "npr" | "nthroot" | "roundto" | "solve" => Some(Arity::exact(2)),
+            "if" | "modpow" => Some(Arity::exact(3)),
+            "round" | "zeros" => Some(Arity::range(1, 2)),
+            name => Aggregate::from_name(name).map(Aggregate::arity),
+        }
+    }
+
+    /// Grabs the next lexer token and its span, or throws an error if none is found.
+    fn next(&mut self) -> Result<(Token, Span), Error> {
+        let span = Span::new(self.end, self.end);
+        self.lexer.next().unwrap_or(Err(Error::UnexpectedEnd { span }))
     }
 
     /// Grabs the next lexer token, and returns it if it was expected or
     /// otherwise throws an error.
-    fn next_expect(&mut self, expect: Option<Token>) -> Result<Option<Token>, Error> {
+    fn next_expect(&mut self, expect: Option<Token>) -> Result<Option<(Token, Span)>, Error> {
         if let Some(t) = expect {
-            let token = self.next()?;
+            let (token, span) = self.next()?;
             if token == t {
-                Ok(Some(token))
+                Ok(Some((token, span)))
             } else {
-                Err(Error::Parse(format!("Expected token {}, found {}", t, token)))
+                Err(Error::UnexpectedToken { found: token, expected: Expected::Token(t), span })
             }
-        } else if let Some(token) = self.peek()? {
-            Err(Error::Parse(format!("Unexpected token {}", token)))
+        } else if let Some((found, span)) = self.peek()? {
+            Err(Error::UnexpectedToken { found, expected: Expected::End, span })
         } else {
             Ok(None)
         }
     }
 
     /// Grabs the next lexer token if it satisfies the predicate function
-    fn next_if<F: Fn(&Token) -> bool>(&mut self, predicate: F) -> Option<Token> {
-        self.peek().unwrap_or(None).filter(|t| predicate(&t))?;
+    fn next_if<F: Fn(&Token) -> bool>(&mut self, predicate: F) -> Option<(Token, Span)> {
+        self.peek().unwrap_or(None).filter(|(t, _)| predicate(t))?;
         self.next().ok()
     }
 
+    /// Grabs the assignment target and = sign if the next tokens are name =
+    fn next_if_assignment(&mut self) -> Option<SpannedName> {
+        let mut lookahead = self.lexer.clone();
+        match (lookahead.next(), lookahead.next()) {
+            (Some(Ok((Token::Ident(name), span))), Some(Ok((Token::Equals, _)))) => {
+                self.lexer = lookahead;
+                Some((name, span))
+            }
+            _ => None,
+        }
+    }
+
+    /// Grabs the function signature and = sign if the next tokens are
+    /// name(param, ...) =
+    fn next_if_definition(&mut self) -> Option<(SpannedName, Vec<SpannedName>)> {
+        let mut lookahead = self.lexer.clone();
+        let name = match (lookahead.next(), lookahead.next()) {
+            (Some(Ok((Token::Ident(name), span))), Some(Ok((Token::OpenParen, _)))) => (name, span),
+            _ => return None,
+        };
+        let mut params = Vec::new();
+        loop {
+            match lookahead.next() {
+                Some(Ok((Token::CloseParen, _))) if params.is_empty() => break,
+                Some(Ok((Token::Ident(param), span))) => params.push((param, span)),
+                _ => return None,
+            }
+            match lookahead.next() {
+                Some(Ok((Token::CloseParen, _))) => break,
+                Some(Ok((Token::Comma, _))) => {}
+                _ => return None,
+            }
+        }
+        match lookahead.next() {
+            Some(Ok((Token::Equals, _))) => {
+                self.lexer = lookahead;
+                Some((name, params))
+            }
+            _ => None,
+        }
+    }
+
+    /// Grabs a base if the next tokens are hex, oct, bin, dec, or base followed
+    /// by a number from 2 to 36
+    fn next_if_base(&mut self) -> Result<Option<u32>, Error> {
+        let base = match self.peek()? {
+            Some((Token::Ident(name), _)) if name == "base" => None,
+            Some((Token::Ident(name), _)) => match name.as_str() {
+                "bin" => Some(2),
+                "oct" => Some(8),
+                "dec" => Some(10),
+                "hex" => Some(16),
+                _ => return Ok(None),
+            },
+            _ => return Ok(None),
+        };
+        self.next()?;
+        if base.is_some() {
+            return Ok(base);
+        }
+        match self.next()? {
+            (Token::Number(n), span) => match n.parse::<u32>() {
+                Ok(base) if (2..=36).contains(&base) => Ok(Some(base)),
+                _ => Err(Error::InvalidBase { base: n, span }),
+            },
+            (found, span) => Err(Error::UnexpectedToken { found, expected: Expected::Value, span }),
+        }
+    }
+
+    /// Grabs a rounding mode if the function is a rounding function and the
+    /// next tokens are a rounding mode closing its arguments, e.g. half_even)
+    fn next_if_rounding(&mut self, function: &str) -> Option<Rounding> {
+        if !matches!(function.to_lowercase().as_str(), "round" | "roundto") {
+            return None;
+        }
+        let mut lookahead = self.lexer.clone();
+        let rounding = match lookahead.next() {
+            Some(Ok((Token::Ident(name), _))) => match name.as_str() {
+                "half_away" => Rounding::HalfUp,
+                "half_even" => Rounding::HalfEven,
+                "toward_zero" => Rounding::Down,
+                _ => return None,
+            },
+            _ => return None,
+        };
+        match lookahead.peek() {
+            Some(Ok((Token::CloseParen, _))) => {
+                self.lexer = lookahead;
+                Some(rounding)
+            }
+            _ => None,
+        }
+    }
+
+    /// Grabs a unit if the next tokens are a unit symbol with an optional
+    /// integer power, e.g. s^-2. If separated, the unit must follow a * or /,
+    /// and its symbol must not be bound to a constant, parameter, or variable.
+    fn next_if_unit(&mut self, separated: bool) -> Option<Unit> {
+        let mut lookahead = self.lexer.clone();
+        let sign = match separated {
+            false => 1,
+            true => match lookahead.next() {
+                Some(Ok((Token::Asterisk, _))) => 1,
+                Some(Ok((Token::Slash, _))) => -1,
+                _ => return None,
+            },
+        };
+        let unit = match lookahead.next() {
+            Some(Ok((Token::Ident(name), _))) if !(separated && self.is_bound(&name)) => {
+                Unit::lookup(&name)?
+            }
+            _ => return None,
+        };
+        self.lexer = lookahead.clone();
+        let mut power = 1;
+        if let Some(Ok((Token::Caret, _))) = lookahead.next() {
+            let negative = lookahead.next_if(|t| matches!(t, Ok((Token::Minus, _)))).is_some();
+            if let Some(Ok((Token::Number(n), _))) = lookahead.next() {
+                if let Ok(p) = n.parse::<i32>() {
+                    self.lexer = lookahead;
+                    power = if negative { -p } else { p };
+                }
+            }
+        }
+        Some(unit.pow(sign * power))
+    }
+
     /// Grabs the next operator token if it satisfies the type and precedence
     fn next_if_operator<T: Operator>(&mut self, min_prec: u8) -> Option<T> {
         let operator = self
             .peek()
             .unwrap_or(None)
-            .and_then(|token| T::from(&token))
+            .and_then(|(token, _)| T::from(&token))
             .filter(|op| op.prec() >= min_prec)?;
         self.next().ok();
         Some(operator)
     }
 
-    /// Peeks the next lexer token if any, but converts it from
-    /// Option<Result<Token, Error>> to Result<Option<Token>, Error> which is
-    /// more convenient to work with (the Iterator trait requires Option<T>).
-    fn peek(&mut self) -> Result<Option<Token>, Error> {
+    /// Peeks the next lexer token and its span if any, but converts it from
+    /// Option<Result<T, Error>> to Result<Option<T>, Error> which is more
+    /// convenient to work with (the Iterator trait requires Option<T>).
+    fn peek(&mut self) -> Result<Option<(Token, Span)>, Error> {
         self.lexer.peek().cloned().map_or(Ok(None), |r| Ok(Some(r?)))
     }
 
@@ -256,35 +645,79 @@ impl<'a> Parser<'a> {
         Ok(expr)
     }
 
-    /// Parses an atom, i.e. a number, constant, function, or parenthesis
+    /// Parses the input string into a statement, i.e. an assignment, a
+    /// function definition, or a bare expression
+    pub fn parse_statement(&mut self) -> Result<Statement, Error> {
+        let statement = if let Some((name, span)) = self.next_if_assignment() {
+            if self.lookup_constant(&name).is_some() {
+                return Err(Error::AssignConstant { name, span });
+            }
+            Statement::Assign { name, expr: self.parse_expression(0)? }
+        } else if let Some((name, params)) = self.next_if_definition() {
+            self.build_definition(name, params)?
+        } else {
+            self.parse_expression(0)?.into()
+        };
+        self.next_expect(None)?;
+        Ok(statement)
+    }
+
+    /// Parses an atom, i.e. a number, constant, function, parenthesis, or list,
+    /// followed by any number of indices
     fn parse_atom(&mut self) -> Result<Expression, Error> {
-        match self.next()? {
-            Token::Ident(name) => {
+        let mut atom = match self.next()? {
+            (Token::Ident(name), span) => {
                 if self.next_if(|t| *t == Token::OpenParen).is_some() {
                     let mut args = Vec::new();
-                    while self.next_if(|t| *t == Token::CloseParen).is_none() {
+                    let mut rounding = None;
+                    let close = loop {
+                        if let Some((_, close)) = self.next_if(|t| *t == Token::CloseParen) {
+                            break close;
+                        }
                         if !args.is_empty() {
                             self.next_expect(Some(Token::Comma))?;
+                            rounding = self.next_if_rounding(&name);
+                            if rounding.is_some() {
+                                continue;
+                            }
                         }
                         args.push(self.parse_expression(0)?);
-                    }
-                    self.build_function(name, args)
+                    };
+                    self.build_function(name, args, rounding, span.join(close))
                 } else {
-                    self.build_constant(name)
+                    self.build_constant(name, span)
                 }
             }
-            Token::Number(n) => self.build_number(n),
-            Token::OpenParen => {
+            (Token::Number(n), span) => self.build_number(n, span),
+            (Token::OpenParen, _) => {
                 let expr = self.parse_expression(0)?;
                 self.next_expect(Some(Token::CloseParen))?;
                 Ok(expr)
             }
-            t => Err(Error::Parse(format!("Expected value, found {}", t))),
+            (Token::OpenBracket, _) => {
+                let mut elements = Vec::new();
+                while self.next_if(|t| *t == Token::CloseBracket).is_none() {
+                    if !elements.is_empty() {
+                        self.next_expect(Some(Token::Comma))?;
+                    }
+                    elements.push(self.parse_expression(0)?);
+                }
+                Ok(Expression::List(elements))
+            }
+            (found, span) => Err(Error::UnexpectedToken { found, expected: Expected::Value, span }),
+        }?;
+        // Indices bind tighter than any operator, e.g. -v[0] is -(v[0])
+        while self.next_if(|t| *t == Token::OpenBracket).is_some() {
+            let index = self.parse_expression(0)?;
+            self.next_expect(Some(Token::CloseBracket))?;
+            atom = Expression::Index { value: atom.into(), index: index.into() };
         }
+        Ok(atom)
     }
 
     /// Parses an expression consisting of at least one atom operated on by any
-    /// number of operators. Uses precedence climbing.
+    /// number of operators, and optionally converted to a unit with to or in.
+    /// Uses precedence climbing.
     fn parse_expression(&mut self, min_prec: u8) -> Result<Expression, Error> {
         let mut lhs = if let Some(prefix) = self.next_if_operator::<PrefixOperator>(min_prec) {
             prefix.build(self.parse_expression(prefix.prec() + prefix.assoc())?)
@@ -294,9 +727,48 @@ impl<'a> Parser<'a> {
         while let Some(postfix) = self.next_if_operator::<PostfixOperator>(min_prec) {
             lhs = postfix.build(lhs)
         }
+        // The RHS of the last comparison, for chained comparisons such that e.g.
+        // a < b <= c means a < b and b <= c
+        let mut chain: Option<Expression> = None;
         while let Some(infix) = self.next_if_operator::<InfixOperator>(min_prec) {
-            lhs = infix.build(lhs, self.parse_expression(infix.prec() + infix.assoc())?)
+            let rhs = self.parse_expression(infix.prec() + infix.assoc())?;
+            let next = Some(&rhs).filter(|_| infix.is_comparison()).cloned();
+            lhs = match chain.take() {
+                Some(prev) if infix.is_comparison() => {
+                    let comparison = infix.build(prev, rhs);
+                    Expression::And { lhs: lhs.into(), rhs: comparison.into() }
+                }
+                _ => infix.build(lhs, rhs),
+            };
+            chain = next;
+        }
+        // Conversions apply to the entire expression, e.g. 1 km + 200 m to m
+        let conversion =
+            |t: &Token| matches!(t, Token::Ident(name) if name == "to" || name == "in");
+        while min_prec == 0 && self.next_if(conversion).is_some() {
+            if let Some(base) = self.next_if_base()? {
+                lhs = Expression::ConvertBase { value: lhs.into(), base };
+                continue;
+            }
+            let unit = match self.parse_unit() {
+                Some(unit) => unit,
+                None => {
+                    let (found, span) = self.next()?;
+                    return Err(Error::UnexpectedToken { found, expected: Expected::Unit, span });
+                }
+            };
+            lhs = Expression::Convert { value: lhs.into(), unit };
         }
         Ok(lhs)
     }
+
+    /// Parses a unit if any, i.e. unit symbols with optional integer powers
+    /// separated by *, /, or whitespace, e.g. kg m/s^2
+    fn parse_unit(&mut self) -> Option<Unit> {
+        let mut unit = self.next_if_unit(false)?;
+        while let Some(term) = self.next_if_unit(false).or_else(|| self.next_if_unit(true)) {
+            unit = unit.combine(&term, 1);
+        }
+        Some(unit)
+    }
 }
@@ -0,0 +1,78 @@
+use crate::environment::Environment;
+use crate::error::EvalError;
+use crate::expression::Expression;
+use crate::function::Function;
+use crate::value::Value;
+
+/// A statement, i.e. a single line of input
+#[derive(Clone, Debug)]
+pub enum Statement {
+    /// Assigns the value of an expression to a variable
+    Assign { name: String, expr: Expression },
+
+    /// Defines a function, registering it in the environment
+    Define(Function),
+
+    /// Evaluates an expression
+    Expression(Expression),
+}
+
+impl From<Expression> for Statement {
+    fn from(expr: Expression) -> Self {
+        Statement::Expression(expr)
+    }
+}
+
+impl Statement {
+    /// Returns the base the resulting value should be displayed in, if the
+    /// statement converts it to one, e.g. 255 to hex
+    pub fn base(&self) -> Option<u32> {
+        match self {
+            Statement::Assign { expr, .. } | Statement::Expression(expr) => expr.base(),
+            Statement::Define(_) => None,
+        }
+    }
+
+    /// Returns true if the resulting value should be displayed as its prime
+    /// factorization, e.g. factor(24)
+    pub fn factors(&self) -> bool {
+        match self {
+            Statement::Assign { expr, .. } | Statement::Expression(expr) => expr.factors(),
+            Statement::Define(_) => false,
+        }
+    }
+
+    /// Executes the statement in the given environment, returning the resulting
+    /// value if any. Returns None for invalid use of units, e.g. 3 m + 2 s.
+    pub fn execute(&self, env: &mut Environment) -> Option<Value> {
+        self.execute_with(env, false).unwrap_or(None)
+    }
+
+    /// Executes the statement in the given environment using strict
+    /// evaluation, returning the resulting value if any. Variables are not
+    /// assigned on errors.
+    pub fn try_execute(&self, env: &mut Environment) -> Result<Option<Value>, EvalError> {
+        self.execute_with(env, true)
+    }
+
+    /// Executes the statement, with strict evaluation if requested. Only
+    /// returns errors in strict mode, or for invalid use of units.
+    pub(crate) fn execute_with(
+        &self,
+        env: &mut Environment,
+        strict: bool,
+    ) -> Result<Option<Value>, EvalError> {
+        match self {
+            Statement::Assign { name, expr } => {
+                let value = expr.evaluate_with(env, &[], strict)?;
+                env.set(name, value.clone());
+                Ok(Some(value))
+            }
+            Statement::Define(function) => {
+                env.define(function.clone());
+                Ok(None)
+            }
+            Statement::Expression(expr) => expr.evaluate_with(env, &[], strict).map(Some),
+        }
+    }
+}
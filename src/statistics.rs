@@ -0,0 +1,198 @@
+use std::cmp::{Ordering, Reverse};
+
+use num::ToPrimitive;
+
+use crate::function::Arity;
+use crate::value::Value;
+
+/// A function of any number of values, such as their sum or median
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Aggregate {
+    /// The largest value
+    Max,
+    /// The arithmetic mean
+    Mean,
+    /// The middle value, or the mean of the two middle values
+    Median,
+    /// The smallest value
+    Min,
+    /// The most common value, or the smallest of the most common values
+    Mode,
+    /// The value below which the given percentage of values fall, taking the
+    /// percentage as first argument and interpolating between values
+    Percentile,
+    /// The population standard deviation
+    PopulationStdev,
+    /// The population variance
+    PopulationVariance,
+    /// The product of the values
+    Product,
+    /// The sample standard deviation, using Bessel's correction
+    SampleStdev,
+    /// The sample variance, using Bessel's correction
+    SampleVariance,
+    /// The sum of the values
+    Sum,
+}
+
+impl Aggregate {
+    /// Applies the function to its arguments, which have been flattened and
+    /// converted to the same unit. Returns the index of an argument outside of the domain, i.e.
+    /// a complex value where they are ordered, or a sample of only one value.
+    pub fn apply(self, args: &[Value]) -> Result<Value, usize> {
+        use Aggregate::*;
+        let values = match self {
+            Percentile => args.get(1..).unwrap_or(&[]),
+            _ => args,
+        };
+        // Empty lists have an empty sum and product, but no other statistics
+        if values.is_empty() {
+            return Ok(match self {
+                Product => Value::from(1),
+                Sum => Value::from(0),
+                _ => Value::Float(f64::NAN),
+            });
+        }
+        // Offsets indices into the values to indices into the arguments
+        let offset = args.len() - values.len();
+        let ordered = matches!(
+            self,
+            Max | Median
+                | Min
+                | Mode
+                | Percentile
+                | PopulationStdev
+                | PopulationVariance
+                | SampleStdev
+                | SampleVariance
+        );
+        if let Some(i) = values.iter().position(Value::is_complex).filter(|_| ordered) {
+            return Err(offset + i);
+        }
+        if ordered && values.iter().any(Value::is_nan) {
+            return Ok(Value::Float(f64::NAN));
+        }
+        let mut sorted = values.to_vec();
+        sorted.sort_by(|a, b| a.compare(b).unwrap_or(Ordering::Equal));
+        let count = Value::from(values.len() as i64);
+        Ok(match self {
+            Max => sorted[sorted.len() - 1].clone(),
+            Mean => Self::sum(values).divide(&count),
+            Median => {
+                let middle = sorted.len() / 2;
+                match sorted.len() % 2 {
+                    1 => sorted[middle].clone(),
+                    _ => Self::mean(&sorted[middle - 1], &sorted[middle]),
+                }
+            }
+            Min => sorted[0].clone(),
+            Mode => {
+                let mut runs = sorted.chunk_by(|a, b| a.equals(b)).collect::<Vec<_>>();
+                // Stable, so that the smallest of the most common values is first
+                runs.sort_by_key(|run| Reverse(run.len()));
+                runs[0][0].clone()
+            }
+            Percentile => {
+                let p = &args[0];
+                let at_least = |n: i64| p.compare(&Value::from(n)).is_some_and(Ordering::is_ge);
+                let at_most = |n: i64| p.compare(&Value::from(n)).is_some_and(Ordering::is_le);
+                match at_least(0) && at_most(100) {
+                    true => Self::percentile(&sorted, p),
+                    false => return Err(0),
+                }
+            }
+            PopulationStdev => Self::variance(values, &count).sqrt(),
+            PopulationVariance => Self::variance(values, &count),
+            Product => values[1..].iter().fold(values[0].clone(), |product, n| &product * n),
+            SampleStdev | SampleVariance if values.len() < 2 => return Err(offset),
+            SampleStdev => Self::variance(values, &(&count - &Value::from(1))).sqrt(),
+            SampleVariance => Self::variance(values, &(&count - &Value::from(1))),
+            Sum => Self::sum(values),
+        })
+    }
+
+    /// Returns the number of arguments accepted by the function
+    pub fn arity(self) -> Arity {
+        match self {
+            Aggregate::Percentile => Arity::variadic(2),
+            _ => Arity::variadic(1),
+        }
+    }
+
+    /// Looks up a function by name, case insensitively
+    pub fn from_name(name: &str) -> Option<Self> {
+        use Aggregate::*;
+        match name.to_lowercase().as_str() {
+            "max" => Some(Max),
+            "mean" => Some(Mean),
+            "median" => Some(Median),
+            "min" => Some(Min),
+            "mode" => Some(Mode),
+            "percentile" => Some(Percentile),
+            "product" => Some(Product),
+            "pstdev" => Some(PopulationStdev),
+            "pvariance" => Some(PopulationVariance),
+            "stdev" => Some(SampleStdev),
+            "sum" => Some(Sum),
+            "variance" => Some(SampleVariance),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of the function
+    pub fn name(self) -> &'static str {
+        use Aggregate::*;
+        match self {
+            Max => "max",
+            Mean => "mean",
+            Median => "median",
+            Min => "min",
+            Mode => "mode",
+            Percentile => "percentile",
+            PopulationStdev => "pstdev",
+            PopulationVariance => "pvariance",
+            Product => "product",
+            SampleStdev => "stdev",
+            SampleVariance => "variance",
+            Sum => "sum",
+        }
+    }
+
+    /// Returns the mean of two values
+    fn mean(a: &Value, b: &Value) -> Value {
+        (a + b).divide(&Value::from(2))
+    }
+
+    /// Returns the percentile p of sorted values, interpolating linearly
+    /// between the closest ranks
+    fn percentile(sorted: &[Value], p: &Value) -> Value {
+        let rank = (p * &Value::from(sorted.len() as i64 - 1)).divide(&Value::from(100));
+        let lower = rank.truncate().and_then(|n| n.to_usize()).unwrap_or(0);
+        match sorted.get(lower + 1) {
+            Some(upper) => {
+                let fraction = &rank - &Value::from(lower as i64);
+                &sorted[lower] + &(&(upper - &sorted[lower]) * &fraction)
+            }
+            None => sorted[lower].clone(),
+        }
+    }
+
+    /// Returns the sum of values
+    fn sum(values: &[Value]) -> Value {
+        values[1..].iter().fold(values[0].clone(), |sum, n| &sum + n)
+    }
+
+    /// Returns the sum of squared deviations from the mean of values, divided
+    /// by the given number of degrees of freedom
+    fn variance(values: &[Value], freedom: &Value) -> Value {
+        let mean = Self::sum(values).divide(&Value::from(values.len() as i64));
+        let squares: Vec<Value> = values
+            .iter()
+            .map(|n| {
+                let deviation = n - &mean;
+                &deviation * &deviation
+            })
+            .collect();
+        Self::sum(&squares).divide(freedom)
+    }
+}
@@ -0,0 +1,244 @@
+use std::fmt;
+
+use num::bigint::BigInt;
+use num::rational::BigRational;
+use num::ToPrimitive;
+
+use crate::value::Value;
+
+/// The dimension of a unit, as powers of the SI base quantities: length, mass,
+/// time, electric current, temperature, amount of substance, and luminous
+/// intensity
+pub type Dimension = [i32; 7];
+
+/// The kind of a named unit
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Kind {
+    /// An SI base unit, or the gram
+    Base,
+    /// A named SI derived unit, which compound units of base units simplify to
+    Derived,
+    /// Any other unit
+    Other,
+}
+
+/// Built-in units, as symbol, size in SI units, dimension, kind, and whether
+/// the unit accepts SI prefixes
+#[rustfmt::skip]
+const UNITS: &[(&str, &str, Dimension, Kind, bool)] = &[
+    // SI base units, with the gram in place of the kilogram
+    ("m",    "1",                [1, 0, 0, 0, 0, 0, 0],    Kind::Base,    true),
+    ("g",    "1e-3",             [0, 1, 0, 0, 0, 0, 0],    Kind::Base,    true),
+    ("s",    "1",                [0, 0, 1, 0, 0, 0, 0],    Kind::Base,    true),
+    ("A",    "1",                [0, 0, 0, 1, 0, 0, 0],    Kind::Base,    true),
+    ("K",    "1",                [0, 0, 0, 0, 1, 0, 0],    Kind::Base,    true),
+    ("mol",  "1",                [0, 0, 0, 0, 0, 1, 0],    Kind::Base,    true),
+    ("cd",   "1",                [0, 0, 0, 0, 0, 0, 1],    Kind::Base,    true),
+    // SI derived units
+    ("C",    "1",                [0, 0, 1, 1, 0, 0, 0],    Kind::Derived, true),
+    ("Hz",   "1",                [0, 0, -1, 0, 0, 0, 0],   Kind::Derived, true),
+    ("J",    "1",                [2, 1, -2, 0, 0, 0, 0],   Kind::Derived, true),
+    ("N",    "1",                [1, 1, -2, 0, 0, 0, 0],   Kind::Derived, true),
+    ("Pa",   "1",                [-1, 1, -2, 0, 0, 0, 0],  Kind::Derived, true),
+    ("V",    "1",                [2, 1, -3, -1, 0, 0, 0],  Kind::Derived, true),
+    ("W",    "1",                [2, 1, -3, 0, 0, 0, 0],   Kind::Derived, true),
+    ("Ω",    "1",                [2, 1, -3, -2, 0, 0, 0],  Kind::Derived, true),
+    ("ohm",  "1",                [2, 1, -3, -2, 0, 0, 0],  Kind::Other,   true),
+    // Other metric units
+    ("L",    "1e-3",             [3, 0, 0, 0, 0, 0, 0],    Kind::Other,   true),
+    ("atm",  "101325",           [-1, 1, -2, 0, 0, 0, 0],  Kind::Other,   false),
+    ("bar",  "1e5",              [-1, 1, -2, 0, 0, 0, 0],  Kind::Other,   true),
+    ("cal",  "4.184",            [2, 1, -2, 0, 0, 0, 0],   Kind::Other,   true),
+    ("day",  "86400",            [0, 0, 1, 0, 0, 0, 0],    Kind::Other,   false),
+    ("eV",   "1.602176634e-19",  [2, 1, -2, 0, 0, 0, 0],   Kind::Other,   true),
+    ("h",    "3600",             [0, 0, 1, 0, 0, 0, 0],    Kind::Other,   false),
+    ("min",  "60",               [0, 0, 1, 0, 0, 0, 0],    Kind::Other,   false),
+    ("t",    "1e3",              [0, 1, 0, 0, 0, 0, 0],    Kind::Other,   false),
+    // Imperial and US customary units
+    ("ft",   "0.3048",           [1, 0, 0, 0, 0, 0, 0],    Kind::Other,   false),
+    ("gal",  "3.785411784e-3",   [3, 0, 0, 0, 0, 0, 0],    Kind::Other,   false),
+    ("inch", "0.0254",           [1, 0, 0, 0, 0, 0, 0],    Kind::Other,   false),
+    ("lb",   "0.45359237",       [0, 1, 0, 0, 0, 0, 0],    Kind::Other,   false),
+    ("mi",   "1609.344",         [1, 0, 0, 0, 0, 0, 0],    Kind::Other,   false),
+    ("oz",   "0.028349523125",   [0, 1, 0, 0, 0, 0, 0],    Kind::Other,   false),
+    ("yd",   "0.9144",           [1, 0, 0, 0, 0, 0, 0],    Kind::Other,   false),
+];
+
+/// SI prefixes, as symbol and factor
+const PREFIXES: &[(&str, &str)] = &[
+    ("T", "1e12"),
+    ("G", "1e9"),
+    ("M", "1e6"),
+    ("k", "1e3"),
+    ("c", "1e-2"),
+    ("m", "1e-3"),
+    ("µ", "1e-6"),
+    ("u", "1e-6"),
+    ("n", "1e-9"),
+    ("p", "1e-12"),
+];
+
+/// A named unit, possibly with an SI prefix, e.g. km
+#[derive(Clone, Debug, PartialEq)]
+struct Named {
+    symbol: String,
+    factor: Value,
+    dimension: Dimension,
+    kind: Kind,
+}
+
+impl Named {
+    /// Looks up a built-in unit by symbol, e.g. m or km
+    fn lookup(symbol: &str) -> Option<Self> {
+        let named =
+            |(name, factor, dimension, kind, _): &(&str, &str, Dimension, Kind, bool)| Named {
+                symbol: name.to_string(),
+                factor: Value::parse(factor).expect("invalid unit factor"),
+                dimension: *dimension,
+                kind: *kind,
+            };
+        if let Some(unit) = UNITS.iter().find(|u| u.0 == symbol) {
+            return Some(named(unit));
+        }
+        PREFIXES.iter().find_map(|(prefix, factor)| {
+            let unit = UNITS.iter().find(|u| u.4 && symbol.strip_prefix(prefix) == Some(u.0))?;
+            let named = named(unit);
+            let factor = Value::parse(factor).expect("invalid prefix factor");
+            Some(Named { symbol: symbol.to_string(), factor: &factor * &named.factor, ..named })
+        })
+    }
+}
+
+/// A unit of measurement, i.e. a product of named units raised to integer
+/// powers, e.g. m/s^2. The empty product is dimensionless.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Unit {
+    terms: Vec<(Named, i32)>,
+}
+
+/// Formats the unit with positive powers first, e.g. kg*m/s^2, or with only
+/// negative powers, e.g. s^-1
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let term = |(named, power): &(Named, i32)| match power {
+            1 => named.symbol.clone(),
+            p => format!("{}^{}", named.symbol, p),
+        };
+        let (numer, denom): (Vec<_>, Vec<_>) = self.terms.iter().partition(|(_, p)| *p > 0);
+        if numer.is_empty() && denom.is_empty() {
+            write!(f, "1")
+        } else if numer.is_empty() {
+            write!(f, "{}", denom.into_iter().map(term).collect::<Vec<_>>().join("*"))
+        } else {
+            write!(f, "{}", numer.into_iter().map(term).collect::<Vec<_>>().join("*"))?;
+            for (named, power) in denom {
+                write!(f, "/{}", term(&(named.clone(), -power)))?;
+            }
+            Ok(())
+        }
+    }
+}
+
+impl Unit {
+    /// Looks up a built-in unit by symbol, e.g. m or km
+    pub fn lookup(symbol: &str) -> Option<Self> {
+        Named::lookup(symbol).map(|named| Unit { terms: vec![(named, 1)] })
+    }
+
+    /// Multiplies the unit by another raised to the given power, e.g. -1 for
+    /// division. Terms are only combined if they are the same named unit.
+    pub fn combine(&self, rhs: &Unit, power: i32) -> Unit {
+        let mut terms = self.terms.clone();
+        for (named, p) in &rhs.terms {
+            match terms.iter_mut().find(|(n, _)| n.symbol == named.symbol) {
+                Some((_, q)) => *q += p * power,
+                None => terms.push((named.clone(), p * power)),
+            }
+        }
+        terms.retain(|(_, p)| *p != 0);
+        Unit { terms }
+    }
+
+    /// Returns the dimension of the unit
+    pub fn dimension(&self) -> Dimension {
+        let mut dimension = Dimension::default();
+        for (named, power) in &self.terms {
+            for (d, n) in dimension.iter_mut().zip(&named.dimension) {
+                *d += n * power;
+            }
+        }
+        dimension
+    }
+
+    /// Returns the size of the unit in SI units
+    pub fn factor(&self) -> Value {
+        self.terms
+            .iter()
+            .fold(Value::from(1), |f, (named, p)| &f * &named.factor.pow(&Value::from(*p as i64)))
+    }
+
+    /// Returns true if the unit has no dimension, e.g. m/km
+    pub fn is_dimensionless(&self) -> bool {
+        self.dimension() == Dimension::default()
+    }
+
+    /// Raises the unit to an integer power
+    pub fn pow(&self, power: i32) -> Unit {
+        Unit::default().combine(self, power)
+    }
+
+    /// Raises the unit to a rational power, if all its powers remain integers,
+    /// e.g. m^2 to the power of 1/2 gives m
+    pub fn pow_ratio(&self, power: &BigRational) -> Option<Unit> {
+        let mut terms = Vec::new();
+        for (named, p) in &self.terms {
+            let q = power * BigRational::from_integer(BigInt::from(*p));
+            match q.is_integer() {
+                true => terms.push((named.clone(), q.to_integer().to_i32()?)),
+                false => return None,
+            }
+        }
+        terms.retain(|(_, p)| *p != 0);
+        Some(Unit { terms })
+    }
+
+    /// Returns the square root of the unit, if all its powers are even
+    pub fn sqrt(&self) -> Option<Unit> {
+        let terms = self.terms.iter().map(|(named, p)| match p % 2 {
+            0 => Some((named.clone(), p / 2)),
+            _ => None,
+        });
+        Some(Unit { terms: terms.collect::<Option<_>>()? })
+    }
+
+    /// Reduces the unit after multiplication or division, returning the reduced
+    /// unit and the scale to multiply the magnitude by. Named units of the same
+    /// dimension are converted to the first of them, e.g. km*m gives km^2, and
+    /// compounds of base units are simplified to a derived unit of the same
+    /// dimension if any, e.g. kg*m/s^2 gives N.
+    pub fn reduce(&self) -> (Unit, Value) {
+        let mut terms: Vec<(Named, i32)> = Vec::new();
+        let mut scale = Value::from(1);
+        for (named, power) in &self.terms {
+            match terms.iter_mut().find(|(n, _)| n.dimension == named.dimension) {
+                Some((n, p)) => {
+                    let ratio = named.factor.divide(&n.factor);
+                    scale = &scale * &ratio.pow(&Value::from(*power as i64));
+                    *p += power;
+                }
+                None => terms.push((named.clone(), *power)),
+            }
+        }
+        terms.retain(|(_, p)| *p != 0);
+        let unit = Unit { terms };
+        if unit.terms.len() > 1 && unit.terms.iter().all(|(n, _)| n.kind == Kind::Base) {
+            let dimension = unit.dimension();
+            let derived = UNITS.iter().find(|u| u.3 == Kind::Derived && u.2 == dimension);
+            if let Some(derived) = derived.and_then(|u| Unit::lookup(u.0)) {
+                let scale = &scale * &unit.factor().divide(&derived.factor());
+                return (derived, scale);
+            }
+        }
+        (unit, scale)
+    }
+}
@@ -0,0 +1,926 @@
+extern crate num;
+
+use std::cmp::Ordering;
+use std::f64;
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+
+use num::bigint::BigInt;
+use num::complex::Complex64;
+use num::rational::BigRational;
+use num::{FromPrimitive, Integer, One, Signed, ToPrimitive, Zero};
+
+use crate::decimal::{Decimal, Rounding};
+use crate::gamma;
+use crate::lexer::split_radix;
+use crate::unit::Unit;
+
+/// The maximum number of bits in an exact integer result, beyond which
+/// operations fall back to floating point
+pub(crate) const MAX_BITS: usize = 1 << 20;
+
+/// The numeric mode used during evaluation
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Mode {
+    /// Values are arbitrary-precision decimals, rounded to the given number of
+    /// significant digits after each operation, falling back to floating point
+    /// for operations with irrational results
+    Decimal { precision: usize, rounding: Rounding },
+    /// Integers and decimals are exact, arbitrary-precision rational values,
+    /// falling back to floating point for operations with irrational results
+    Exact,
+    /// Values are two's complement integers with the given number of bits,
+    /// which are truncated towards zero and either wrap around or are checked
+    /// on overflow. Bitwise operations always wrap around.
+    Fixed { bits: u32, signed: bool, checked: bool },
+    /// All values are 64-bit floating point numbers
+    #[default]
+    Float,
+}
+
+/// A numeric value
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    /// A complex number with 64-bit floating point parts, which always has a
+    /// nonzero imaginary part
+    Complex(Complex64),
+    /// An arbitrary-precision decimal number
+    Decimal(Decimal),
+    /// A 64-bit floating point number
+    Float(f64),
+    /// An exact, arbitrary-precision integer
+    Integer(BigInt),
+    /// A list of values, e.g. [1, 2, 3], which arithmetic applies to
+    /// element-wise
+    List(Vec<Value>),
+    /// A value with a unit of measurement, which is never dimensionless
+    Quantity(Box<Value>, Unit),
+    /// An exact, arbitrary-precision rational number, which is never an integer
+    Rational(BigRational),
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Value::Integer((b as i64).into())
+    }
+}
+
+impl From<Complex64> for Value {
+    fn from(n: Complex64) -> Self {
+        if n.im == 0.0 {
+            Value::Float(n.re)
+        } else {
+            Value::Complex(n)
+        }
+    }
+}
+
+impl From<f64> for Value {
+    fn from(n: f64) -> Self {
+        Value::Float(n)
+    }
+}
+
+impl From<BigInt> for Value {
+    fn from(n: BigInt) -> Self {
+        Value::Integer(n)
+    }
+}
+
+impl From<BigRational> for Value {
+    fn from(n: BigRational) -> Self {
+        match n.is_integer() {
+            true => Value::Integer(n.to_integer()),
+            false => Value::Rational(n),
+        }
+    }
+}
+
+impl From<i64> for Value {
+    fn from(n: i64) -> Self {
+        Value::Integer(n.into())
+    }
+}
+
+impl From<&Value> for f64 {
+    fn from(value: &Value) -> Self {
+        value.to_f64()
+    }
+}
+
+impl From<Value> for f64 {
+    fn from(value: Value) -> Self {
+        value.to_f64()
+    }
+}
+
+/// Formats the value. Rational numbers are formatted as fractions, or as mixed
+/// numbers with the alternate flag, e.g. 7/2 or 3 1/2. Complex numbers are
+/// formatted as e.g. 3+4i, quantities as e.g. 9.81 m/s^2, and lists as e.g.
+/// [1, 2, 3].
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Complex(n) => {
+                if n.re != 0.0 {
+                    write!(f, "{}", n.re)?;
+                    if !n.im.is_sign_negative() {
+                        write!(f, "+")?;
+                    }
+                }
+                if n.im == 1.0 {
+                    write!(f, "i")
+                } else if n.im == -1.0 {
+                    write!(f, "-i")
+                } else {
+                    write!(f, "{}i", n.im)
+                }
+            }
+            Value::Decimal(n) => write!(f, "{}", n),
+            Value::Float(n) => write!(f, "{}", n),
+            Value::Integer(n) => write!(f, "{}", n),
+            Value::List(n) => {
+                write!(f, "[")?;
+                for (i, n) in n.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    match f.alternate() {
+                        true => write!(f, "{:#}", n)?,
+                        false => write!(f, "{}", n)?,
+                    }
+                }
+                write!(f, "]")
+            }
+            Value::Quantity(n, unit) if f.alternate() => write!(f, "{:#} {}", n, unit),
+            Value::Quantity(n, unit) => write!(f, "{} {}", n, unit),
+            Value::Rational(n) if f.alternate() && !n.trunc().is_zero() => {
+                write!(f, "{} {}", n.trunc(), n.fract().abs())
+            }
+            Value::Rational(n) => write!(f, "{}", n),
+        }
+    }
+}
+
+/// Adds values, where quantities must have the same unit, see Value::convert
+impl Add for &Value {
+    type Output = Value;
+
+    fn add(self, rhs: Self) -> Value {
+        match (self, rhs) {
+            (Value::Integer(l), Value::Integer(r)) => Value::Integer(l + r),
+            (Value::Quantity(l, unit), r) => {
+                Value::Quantity((&**l + r.magnitude()).into(), unit.clone())
+            }
+            (l, Value::Quantity(r, unit)) => Value::Quantity((l + &**r).into(), unit.clone()),
+            (l, r) if l.is_complex() || r.is_complex() => (l.to_complex() + r.to_complex()).into(),
+            (l, r) => match (l.to_ratio(), r.to_ratio()) {
+                (Some(l), Some(r)) => (l + r).into(),
+                _ => Value::Float(l.to_f64() + r.to_f64()),
+            },
+        }
+    }
+}
+
+impl Mul for &Value {
+    type Output = Value;
+
+    fn mul(self, rhs: Self) -> Value {
+        match (self, rhs) {
+            (Value::Integer(l), Value::Integer(r)) => Value::Integer(l * r),
+            (l, r) if l.unit().is_some() || r.unit().is_some() => {
+                let unit = l.unit().cloned().unwrap_or_default();
+                let (unit, scale) = unit.combine(r.unit().unwrap_or(&Unit::default()), 1).reduce();
+                (&(l.magnitude() * r.magnitude()) * &scale).with_unit(unit)
+            }
+            (l, r) if l.is_complex() || r.is_complex() => (l.to_complex() * r.to_complex()).into(),
+            (l, r) => match (l.to_ratio(), r.to_ratio()) {
+                (Some(l), Some(r)) => (l * r).into(),
+                _ => Value::Float(l.to_f64() * r.to_f64()),
+            },
+        }
+    }
+}
+
+impl Neg for &Value {
+    type Output = Value;
+
+    fn neg(self) -> Value {
+        match self {
+            Value::Complex(n) => Value::Complex(-n),
+            Value::Decimal(n) => Value::Decimal(-n),
+            Value::Float(n) => Value::Float(-n),
+            Value::Integer(n) => Value::Integer(-n),
+            Value::List(n) => Value::List(n.iter().map(|n| -n).collect()),
+            Value::Quantity(n, unit) => Value::Quantity((-&**n).into(), unit.clone()),
+            Value::Rational(n) => Value::Rational(-n),
+        }
+    }
+}
+
+/// Subtracts values, where quantities must have the same unit, see Value::convert
+impl Sub for &Value {
+    type Output = Value;
+
+    fn sub(self, rhs: Self) -> Value {
+        match (self, rhs) {
+            (Value::Integer(l), Value::Integer(r)) => Value::Integer(l - r),
+            (Value::Quantity(l, unit), r) => {
+                Value::Quantity((&**l - r.magnitude()).into(), unit.clone())
+            }
+            (l, Value::Quantity(r, unit)) => Value::Quantity((l - &**r).into(), unit.clone()),
+            (l, r) if l.is_complex() || r.is_complex() => (l.to_complex() - r.to_complex()).into(),
+            (l, r) => match (l.to_ratio(), r.to_ratio()) {
+                (Some(l), Some(r)) => (l - r).into(),
+                _ => Value::Float(l.to_f64() - r.to_f64()),
+            },
+        }
+    }
+}
+
+impl Value {
+    /// Returns the absolute value, i.e. the magnitude of complex numbers
+    pub fn abs(&self) -> Value {
+        match self {
+            Value::Complex(n) => Value::Float(n.norm()),
+            Value::Decimal(n) if n.is_negative() => Value::Decimal(-n),
+            Value::Float(n) => Value::Float(n.abs()),
+            Value::Integer(n) => Value::Integer(n.abs()),
+            Value::Quantity(n, unit) => Value::Quantity(n.abs().into(), unit.clone()),
+            Value::Rational(n) => Value::Rational(n.abs()),
+            n => n.clone(),
+        }
+    }
+
+    /// Returns the bitwise and of two integer values, or NaN otherwise
+    pub fn bit_and(&self, rhs: &Value) -> Value {
+        self.bitwise(rhs, |l, r| l & r)
+    }
+
+    /// Returns the bitwise complement of an integer value, or NaN otherwise
+    pub fn bit_not(&self) -> Value {
+        self.bitwise(self, |n, _| !n)
+    }
+
+    /// Returns the bitwise or of two integer values, or NaN otherwise
+    pub fn bit_or(&self, rhs: &Value) -> Value {
+        self.bitwise(rhs, |l, r| l | r)
+    }
+
+    /// Returns the bitwise exclusive or of two integer values, or NaN otherwise
+    pub fn bit_xor(&self, rhs: &Value) -> Value {
+        self.bitwise(rhs, |l, r| l ^ r)
+    }
+
+    /// Returns the argument of the value, i.e. the angle of complex numbers
+    pub fn arg(&self) -> Value {
+        Value::Float(self.to_complex().arg())
+    }
+
+    /// Returns the complex conjugate of the value
+    pub fn conj(&self) -> Value {
+        match self {
+            Value::Complex(n) => Value::Complex(n.conj()),
+            Value::Quantity(n, unit) => Value::Quantity(n.conj().into(), unit.clone()),
+            n => n.clone(),
+        }
+    }
+
+    /// Converts a quantity to the given unit, or None if the dimensions differ.
+    /// Values without a unit are dimensionless.
+    pub fn convert(&self, unit: &Unit) -> Option<Value> {
+        let from = self.unit().cloned().unwrap_or_default();
+        if from.dimension() != unit.dimension() {
+            return None;
+        }
+        let scale = from.factor().divide(&unit.factor());
+        Some((self.magnitude() * &scale).with_unit(unit.clone()))
+    }
+
+    /// Compares the value to another, exactly for exact values and by
+    /// magnitude for quantities of the same unit. Returns None if either value
+    /// is NaN or complex.
+    pub fn compare(&self, rhs: &Value) -> Option<Ordering> {
+        match (self.magnitude(), rhs.magnitude()) {
+            (l, r) if l.is_complex() || r.is_complex() => None,
+            (l, r) => match (l.to_ratio(), r.to_ratio()) {
+                (Some(l), Some(r)) => Some(l.cmp(&r)),
+                _ => l.to_f64().partial_cmp(&r.to_f64()),
+            },
+        }
+    }
+
+    /// Divides the value by another. Exact values are divided exactly, unless
+    /// the divisor is zero.
+    pub fn divide(&self, rhs: &Value) -> Value {
+        match (self, rhs) {
+            (l, r) if l.unit().is_some() || r.unit().is_some() => {
+                let unit = l.unit().cloned().unwrap_or_default();
+                let (unit, scale) = unit.combine(r.unit().unwrap_or(&Unit::default()), -1).reduce();
+                (&l.magnitude().divide(r.magnitude()) * &scale).with_unit(unit)
+            }
+            (Value::Integer(l), Value::Integer(r)) if !r.is_zero() && l.is_multiple_of(r) => {
+                Value::Integer(l / r)
+            }
+            (l, r) if l.is_complex() || r.is_complex() => (l.to_complex() / r.to_complex()).into(),
+            (l, r) => match (l.to_ratio(), r.to_ratio()) {
+                (Some(l), Some(r)) if !r.is_zero() => (l / r).into(),
+                _ => Value::Float(l.to_f64() / r.to_f64()),
+            },
+        }
+    }
+
+    /// Returns true if the value is numerically equal to another, e.g. 0.5 and
+    /// 1/2, comparing quantities of the same unit by magnitude and lists by
+    /// element
+    pub fn equals(&self, rhs: &Value) -> bool {
+        match (self.magnitude(), rhs.magnitude()) {
+            (Value::List(l), Value::List(r)) => {
+                l.len() == r.len() && l.iter().zip(r).all(|(l, r)| l.equals(r))
+            }
+            (l, r) if l.is_complex() || r.is_complex() => l.to_complex() == r.to_complex(),
+            (l, r) => l.compare(r) == Some(Ordering::Equal),
+        }
+    }
+
+    /// Returns the factorial of the value, i.e. gamma(n + 1). Exact integers give
+    /// exact results, unless the result has too many bits to represent, which is
+    /// known from its logarithm without computing it.
+    pub fn factorial(&self) -> Value {
+        match self.to_integer().and_then(|n| n.to_u64()) {
+            Some(n) if gamma::factorial_bits(n as f64) <= MAX_BITS as f64 => {
+                Value::Integer((1..=n).fold(BigInt::one(), |a, b| a * b))
+            }
+            Some(_) => Value::Float(f64::INFINITY),
+            None => Value::Float(gamma::gamma(self.to_f64() + 1.0)),
+        }
+    }
+
+    /// Returns the fractional part of the value, with the same sign, e.g. -0.5
+    /// for -2.5. Quantities keep their unit.
+    pub fn fract(&self) -> Value {
+        self - &self.round_to(&Value::Integer(BigInt::one()), Rounding::Down)
+    }
+
+    /// Returns the hypotenuse of a right triangle with the values as legs, i.e.
+    /// sqrt(|a|^2 + |b|^2), where quantities must have the same unit
+    pub fn hypot(&self, rhs: &Value) -> Value {
+        let hypot =
+            Value::Float(self.magnitude().abs().to_f64().hypot(rhs.magnitude().abs().to_f64()));
+        match self.unit().or_else(|| rhs.unit()) {
+            Some(unit) => hypot.with_unit(unit.clone()),
+            None => hypot,
+        }
+    }
+
+    /// Returns the imaginary part of the value
+    pub fn im(&self) -> Value {
+        match self {
+            Value::Complex(n) => Value::Float(n.im),
+            Value::Quantity(n, unit) => n.im().with_unit(unit.clone()),
+            _ => Value::Integer(BigInt::zero()),
+        }
+    }
+
+    /// Returns true if the value is a complex number, or a quantity with a
+    /// complex magnitude
+    pub fn is_complex(&self) -> bool {
+        matches!(self.magnitude(), Value::Complex(_))
+    }
+
+    /// Returns true if the value, or any part of it, is infinite
+    pub fn is_infinite(&self) -> bool {
+        match self {
+            Value::Complex(n) => n.is_infinite(),
+            Value::Float(n) => n.is_infinite(),
+            Value::List(n) => n.iter().any(Value::is_infinite),
+            Value::Quantity(n, _) => n.is_infinite(),
+            _ => false,
+        }
+    }
+
+    /// Returns true if the value is a whole number
+    pub fn is_integer(&self) -> bool {
+        match self {
+            Value::Complex(_) => false,
+            Value::Decimal(n) => n.is_integer(),
+            Value::Float(n) => n.fract() == 0.0,
+            Value::Integer(_) => true,
+            Value::List(_) => false,
+            Value::Quantity(n, _) => n.is_integer(),
+            Value::Rational(_) => false,
+        }
+    }
+
+    /// Returns true if the value, or any part of it, is not a number
+    pub fn is_nan(&self) -> bool {
+        match self {
+            Value::Complex(n) => n.is_nan(),
+            Value::Float(n) => n.is_nan(),
+            Value::List(n) => n.iter().any(Value::is_nan),
+            Value::Quantity(n, _) => n.is_nan(),
+            _ => false,
+        }
+    }
+
+    /// Returns true if the value is real and less than zero
+    pub fn is_negative(&self) -> bool {
+        match self {
+            Value::Complex(_) => false,
+            Value::Decimal(n) => n.is_negative(),
+            Value::Float(n) => *n < 0.0,
+            Value::Integer(n) => n.is_negative(),
+            Value::List(_) => false,
+            Value::Quantity(n, _) => n.is_negative(),
+            Value::Rational(n) => n.is_negative(),
+        }
+    }
+
+    /// Returns true if the value is zero
+    pub fn is_zero(&self) -> bool {
+        match self {
+            Value::Complex(_) => false,
+            Value::Decimal(n) => n.is_zero(),
+            Value::Float(n) => *n == 0.0,
+            Value::Integer(n) => n.is_zero(),
+            Value::List(_) => false,
+            Value::Quantity(n, _) => n.is_zero(),
+            Value::Rational(_) => false,
+        }
+    }
+
+    /// Returns the magnitude of a quantity, or the value itself otherwise
+    pub fn magnitude(&self) -> &Value {
+        match self {
+            Value::Quantity(n, _) => n,
+            n => n,
+        }
+    }
+
+    /// Returns the modulo of the value and another, with the sign of the RHS.
+    /// Quantities must have the same unit, see Value::convert.
+    pub fn modulo(&self, rhs: &Value) -> Value {
+        match (self, rhs) {
+            (Value::Quantity(l, unit), r) => {
+                Value::Quantity(l.modulo(r.magnitude()).into(), unit.clone())
+            }
+            (l, Value::Quantity(r, unit)) => Value::Quantity(l.modulo(r).into(), unit.clone()),
+            (Value::Integer(l), Value::Integer(r)) if !r.is_zero() => {
+                Value::Integer(l.mod_floor(r))
+            }
+            (l, r) => match (l.to_ratio(), r.to_ratio()) {
+                (Some(l), Some(r)) if !r.is_zero() => (&l - &r * (&l / &r).floor()).into(),
+                // The % operator in Rust is remainder, not modulo
+                _ => {
+                    let (l, r) = (l.to_f64(), r.to_f64());
+                    Value::Float(((l % r) + r) % r)
+                }
+            },
+        }
+    }
+
+    /// Parses a number literal, such as 3, 3.14, 3.14e2, 1_000, or 0xFF, to an
+    /// exact value. Literals with exponents too large to represent exactly are
+    /// parsed as floating point numbers, and imaginary literals such as 4i as
+    /// complex numbers.
+    pub fn parse(literal: &str) -> Option<Value> {
+        let literal: &str = &literal.replace('_', "");
+        if let Some((radix, digits)) = split_radix(literal) {
+            return BigInt::parse_bytes(digits.as_bytes(), radix).map(Value::Integer);
+        }
+        if let Some(imaginary) = literal.strip_suffix('i') {
+            let im = Value::parse(imaginary)?.to_f64();
+            return Some(Complex64::new(0.0, im).into());
+        }
+        let (mantissa, exponent) = match literal.find(['e', 'E']) {
+            Some(i) => (&literal[..i], literal[i + 1..].parse::<i64>().ok()?),
+            None => (literal, 0),
+        };
+        let (int, fract) = match mantissa.find('.') {
+            Some(i) => (&mantissa[..i], &mantissa[i + 1..]),
+            None => (mantissa, ""),
+        };
+        let digits = format!("{}{}", int, fract).parse::<BigInt>().ok()?;
+        let exponent = exponent - fract.len() as i64;
+        match exponent.unsigned_abs() as usize {
+            // Each decimal digit takes roughly 3.3 bits
+            e if e.saturating_mul(4) > MAX_BITS => literal.parse::<f64>().ok().map(Value::Float),
+            e if exponent < 0 => {
+                Some(BigRational::new(digits, num::pow(BigInt::from(10), e)).into())
+            }
+            e => Some(Value::Integer(digits * num::pow(BigInt::from(10), e))),
+        }
+    }
+
+    /// Raises the value to the power of another. Exact values are only raised
+    /// exactly for integer exponents, and negative bases with fractional
+    /// exponents give complex results. Quantities must have exponents giving
+    /// integer powers of their units, e.g. (4 m^2)^0.5 is 2 m, or give NaN.
+    pub fn pow(&self, rhs: &Value) -> Value {
+        if let Value::Quantity(n, _) = self {
+            return match self.unit_pow(rhs) {
+                Some(power) => n.pow(rhs).with_unit(power),
+                None => Value::Float(f64::NAN),
+            };
+        }
+        if let (Some(l), Some(r)) = (self.to_ratio(), rhs.to_integer()) {
+            let bits = l.numer().bits() + l.denom().bits();
+            match r.abs().to_usize() {
+                Some(e)
+                    if bits.saturating_mul(e) <= MAX_BITS && !(l.is_zero() && r.is_negative()) =>
+                {
+                    let numer = num::pow(l.numer().clone(), e);
+                    let denom = num::pow(l.denom().clone(), e);
+                    return match r.is_negative() {
+                        false => BigRational::new(numer, denom).into(),
+                        true => BigRational::new(denom, numer).into(),
+                    };
+                }
+                _ => {}
+            }
+        }
+        match (self, rhs) {
+            (l, r) if (l.is_complex() || r.is_complex()) && l.is_zero() => Value::Float(0.0),
+            (l, r) if l.is_complex() && r.is_integer() && r.to_f64().abs() <= i32::MAX as f64 => {
+                l.to_complex().powi(r.to_f64() as i32).into()
+            }
+            (l, r) if l.is_complex() || r.is_complex() => {
+                l.to_complex().powc(r.to_complex()).into()
+            }
+            (l, r) if l.is_negative() && !r.is_infinite() && !r.is_integer() => {
+                l.to_complex().powc(r.to_complex()).into()
+            }
+            (l, r) => Value::Float(l.to_f64().powf(r.to_f64())),
+        }
+    }
+
+    /// Returns true if the value does not fit in the integer width of a
+    /// checked fixed-width mode, once truncated
+    pub fn overflows(&self, mode: Mode) -> bool {
+        if let Value::List(n) = self {
+            return n.iter().any(|n| n.overflows(mode));
+        }
+        match (self.truncate(), mode) {
+            (Some(n), Mode::Fixed { bits, signed, checked: true }) => {
+                Value::wrap(&n, bits, signed) != n
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the real part of the value
+    pub fn re(&self) -> Value {
+        match self {
+            Value::Complex(n) => Value::Float(n.re),
+            Value::Quantity(n, unit) => n.re().with_unit(unit.clone()),
+            n => n.clone(),
+        }
+    }
+
+    /// Returns the real root of the value with the given degree, i.e. the
+    /// negative root for negative values and odd integer degrees, or otherwise
+    /// the principal root. Roots of perfect powers are exact, e.g. the cube
+    /// root of 1000 is 10.
+    pub fn root(&self, degree: &Value) -> Value {
+        let (x, n) = (self.to_f64(), degree.to_f64());
+        let odd = degree.is_integer() && n % 2.0 != 0.0;
+        if self.is_complex() || degree.is_complex() || (x < 0.0 && !odd) {
+            return (self.to_complex().powc(degree.to_complex().inv())).into();
+        }
+        let root = x.abs().powf(1.0 / n);
+        let root = match root.round() {
+            r if degree.is_integer() && r.powf(n) == x.abs() => r,
+            _ => root,
+        };
+        Value::Float(root.copysign(x))
+    }
+
+    /// Rounds the value to the given number of decimals, which must be a
+    /// non-negative integer, with the given rounding mode, see Value::round_to
+    pub fn round(&self, decimals: &Value, rounding: Rounding) -> Value {
+        match decimals.truncate().and_then(|d| d.to_usize()) {
+            Some(d) if d.saturating_mul(4) <= MAX_BITS => {
+                let step = BigRational::new(BigInt::one(), num::pow(BigInt::from(10), d));
+                self.round_to(&step.into(), rounding)
+            }
+            _ => self.clone(),
+        }
+    }
+
+    /// Rounds the value to a multiple of the step with the given rounding mode.
+    /// Exact values are rounded exactly, and floating point numbers by their
+    /// shortest decimal representation, e.g. 2.675 rounds to 2.68 rather than
+    /// 2.67 even though it's stored as 2.67499... Quantities keep their unit,
+    /// and complex numbers are rounded by parts.
+    pub fn round_to(&self, step: &Value, rounding: Rounding) -> Value {
+        let float = matches!(self, Value::Float(_)) || matches!(step, Value::Float(_));
+        match (self, step.magnitude()) {
+            (Value::Quantity(n, unit), step) => {
+                Value::Quantity(n.round_to(step, rounding).into(), unit.clone())
+            }
+            (Value::Complex(n), step) => {
+                let re = Value::Float(n.re).round_to(step, rounding).to_f64();
+                let im = Value::Float(n.im).round_to(step, rounding).to_f64();
+                Complex64::new(re, im).into()
+            }
+            (n, _) if n.is_infinite() || n.is_nan() => n.clone(),
+            (n, step) => match (n.to_decimal_ratio(), step.to_decimal_ratio()) {
+                (Some(n), Some(step)) if !step.is_zero() => {
+                    let step = step.abs();
+                    let rounded = BigRational::from_integer(rounding.round(&(n / &step))) * step;
+                    match float {
+                        true => Value::Float(Value::ratio_to_f64(rounded.numer(), rounded.denom())),
+                        false => rounded.into(),
+                    }
+                }
+                _ => Value::Float(f64::NAN),
+            },
+        }
+    }
+
+    /// Shifts the bits of an integer value left, or returns NaN otherwise.
+    /// Results too large to represent exactly are floating point numbers.
+    pub fn shl(&self, rhs: &Value) -> Value {
+        match (self.truncate(), rhs.truncate().and_then(|r| r.to_usize())) {
+            (Some(l), Some(r)) if l.bits().saturating_add(r) <= MAX_BITS => Value::Integer(l << r),
+            (Some(l), _) if l.is_zero() => Value::Integer(l),
+            (Some(_), _) => Value::Float(self.to_f64() * f64::INFINITY),
+            (None, _) => Value::Float(f64::NAN),
+        }
+    }
+
+    /// Shifts the bits of an integer value right, keeping the sign, or returns
+    /// NaN otherwise
+    pub fn shr(&self, rhs: &Value) -> Value {
+        match (self.truncate(), rhs.truncate()) {
+            (Some(l), Some(r)) => match r.to_usize() {
+                Some(r) => Value::Integer(l >> r),
+                None if l.is_negative() => Value::from(-1),
+                None => Value::from(0),
+            },
+            _ => Value::Float(f64::NAN),
+        }
+    }
+
+    /// Returns the sign of the value, i.e. -1, 0, or 1, or the value divided by
+    /// its absolute value for complex numbers. Quantities are signed by their
+    /// magnitude.
+    pub fn sign(&self) -> Value {
+        match self.magnitude() {
+            Value::Complex(n) if n.is_zero() => Value::Float(0.0),
+            Value::Complex(n) => (n / n.norm()).into(),
+            Value::Float(n) if *n == 0.0 || n.is_nan() => Value::Float(*n),
+            Value::Float(n) => Value::Float(n.signum()),
+            n => match n.compare(&Value::Integer(BigInt::zero())) {
+                Some(ordering) => Value::Integer(BigInt::from(ordering as i8)),
+                None => Value::Float(f64::NAN),
+            },
+        }
+    }
+
+    /// Returns the square root of the value, which is complex for negative
+    /// values. Quantities must have a unit with even powers, or give NaN.
+    pub fn sqrt(&self) -> Value {
+        match self {
+            Value::Complex(n) => n.sqrt().into(),
+            Value::Quantity(n, unit) => match unit.sqrt() {
+                Some(root) => n.sqrt().with_unit(root),
+                None => Value::Float(f64::NAN),
+            },
+            n if n.is_negative() => Value::Complex(Complex64::new(0.0, (-n.to_f64()).sqrt())),
+            n => Value::Float(n.to_f64().sqrt()),
+        }
+    }
+
+    /// Applies a function to the value, using the complex variant for complex
+    /// values and for finite real values outside the domain of the real
+    /// variant, e.g. ln(-1), and the real variant otherwise
+    pub fn map(&self, real: fn(f64) -> f64, complex: fn(Complex64) -> Complex64) -> Value {
+        match self {
+            Value::Complex(n) => complex(*n).into(),
+            n => match (n.to_f64(), real(n.to_f64())) {
+                (x, y) if y.is_nan() && x.is_finite() => complex(Complex64::new(x, 0.0)).into(),
+                (_, y) => Value::Float(y),
+            },
+        }
+    }
+
+    /// Returns the unit of a quantity, or None for dimensionless values
+    pub fn unit(&self) -> Option<&Unit> {
+        match self {
+            Value::Quantity(_, unit) => Some(unit),
+            _ => None,
+        }
+    }
+
+    /// Returns the unit of a quantity raised to the power of a value, or None
+    /// if its powers wouldn't be integers
+    pub fn unit_pow(&self, rhs: &Value) -> Option<Unit> {
+        let power = rhs.to_decimal_ratio()?;
+        self.unit()?.pow_ratio(&power)
+    }
+
+    /// Gives the value a unit. Dimensionless units are removed by scaling the
+    /// value, e.g. 3 km/m gives 3000.
+    pub fn with_unit(self, unit: Unit) -> Value {
+        match unit.is_dimensionless() {
+            true => &self * &unit.factor(),
+            false => Value::Quantity(self.into(), unit),
+        }
+    }
+
+    /// Converts the value to the given mode. In float mode, all real values are
+    /// floating point numbers, while in exact and decimal mode floating point
+    /// and complex numbers are kept as is. Quantities convert their magnitude,
+    /// and lists their elements.
+    pub fn into_mode(self, mode: Mode) -> Value {
+        match (self, mode) {
+            (Value::Complex(n), _) => Value::Complex(n),
+            (Value::List(n), mode) => {
+                Value::List(n.into_iter().map(|n| n.into_mode(mode)).collect())
+            }
+            (Value::Quantity(n, unit), mode) => Value::Quantity(n.into_mode(mode).into(), unit),
+            (value, Mode::Fixed { bits, signed, .. }) => match value.truncate() {
+                Some(n) => Value::Integer(Value::wrap(&n, bits, signed)),
+                None => value,
+            },
+            (Value::Float(n), _) => Value::Float(n),
+            (Value::Decimal(n), Mode::Exact) => n.to_ratio().into(),
+            (value, Mode::Exact) => value,
+            (value, Mode::Float) => Value::Float(value.to_f64()),
+            (Value::Decimal(n), Mode::Decimal { .. }) => Value::Decimal(n),
+            (value, Mode::Decimal { precision, rounding }) => {
+                let n = value.to_ratio().unwrap_or_else(BigRational::zero);
+                Value::Decimal(Decimal::from_ratio(&n, precision, rounding))
+            }
+        }
+    }
+
+    /// Converts the value to a complex number
+    pub fn to_complex(&self) -> Complex64 {
+        match self {
+            Value::Complex(n) => *n,
+            Value::Quantity(n, _) => n.to_complex(),
+            n => Complex64::new(n.to_f64(), 0.0),
+        }
+    }
+
+    /// Converts the value to a f64, which may be infinite for large values, or
+    /// NaN for complex values and lists. Quantities give their magnitude.
+    pub fn to_f64(&self) -> f64 {
+        match self {
+            Value::Complex(_) | Value::List(_) => f64::NAN,
+            Value::Decimal(n) => {
+                let n = n.to_ratio();
+                Value::ratio_to_f64(n.numer(), n.denom())
+            }
+            Value::Float(n) => *n,
+            Value::Integer(n) => Value::ratio_to_f64(n, &BigInt::one()),
+            Value::Quantity(n, _) => n.to_f64(),
+            Value::Rational(n) => Value::ratio_to_f64(n.numer(), n.denom()),
+        }
+    }
+
+    /// Formats the value in the given base from 2 to 36, with uppercase digits
+    /// and fractional digits for non-integers. Hexadecimal, octal, and binary
+    /// numbers have a prefix, e.g. 0xFF, and binary digits are grouped by four.
+    pub fn to_string_radix(&self, radix: u32) -> String {
+        match self {
+            n if radix == 10 => n.to_string(),
+            Value::Complex(n) => {
+                let (re, im) = (Value::Float(n.re), Value::Float(n.im));
+                let (re, im) = (re.to_string_radix(radix), im.to_string_radix(radix));
+                match im.starts_with('-') {
+                    true => format!("{}{}i", re, im),
+                    false => format!("{}+{}i", re, im),
+                }
+            }
+            Value::List(n) => {
+                let n: Vec<String> = n.iter().map(|n| n.to_string_radix(radix)).collect();
+                format!("[{}]", n.join(", "))
+            }
+            Value::Quantity(n, unit) => format!("{} {}", n.to_string_radix(radix), unit),
+            n if n.is_nan() || n.is_infinite() => n.to_string(),
+            n => {
+                let ratio = n.to_ratio().or_else(|| BigRational::from_float(n.to_f64()));
+                let ratio = ratio.unwrap_or_else(BigRational::zero);
+                let sign = if ratio.is_negative() { "-" } else { "" };
+                let prefix = match radix {
+                    2 => "0b",
+                    8 => "0o",
+                    16 => "0x",
+                    _ => "",
+                };
+                let int = ratio.abs().trunc().to_integer().to_str_radix(radix).to_uppercase();
+                let int = match radix {
+                    2 => {
+                        let digits: Vec<char> = int.chars().collect();
+                        let nibbles = digits.rchunks(4).rev().map(|c| c.iter().collect::<String>());
+                        nibbles.collect::<Vec<_>>().join("_")
+                    }
+                    _ => int,
+                };
+                // Enough fractional digits for the precision of a f64
+                let max = (f64::MANTISSA_DIGITS as f64 / (radix as f64).log2()).ceil() as usize;
+                let mut fract = ratio.abs().fract();
+                let mut digits = String::new();
+                while !fract.is_zero() && digits.len() < max {
+                    fract *= BigRational::from_integer(radix.into());
+                    let digit = fract.trunc().to_integer().to_u32().unwrap_or(0);
+                    digits.extend(
+                        std::char::from_digit(digit, radix).map(|c| c.to_ascii_uppercase()),
+                    );
+                    fract = fract.fract();
+                }
+                match digits.is_empty() {
+                    true => format!("{}{}{}", sign, prefix, int),
+                    false => format!("{}{}{}.{}", sign, prefix, int, digits),
+                }
+            }
+        }
+    }
+
+    /// Converts an exact whole number to an integer, or None otherwise
+    pub fn to_integer(&self) -> Option<BigInt> {
+        match self {
+            Value::Decimal(n) if n.is_integer() => Some(n.to_ratio().to_integer()),
+            Value::Integer(n) => Some(n.clone()),
+            _ => None,
+        }
+    }
+
+    /// Converts an exact value to a rational number, or None for floats,
+    /// lists, and quantities
+    pub fn to_ratio(&self) -> Option<BigRational> {
+        match self {
+            Value::Complex(_) => None,
+            Value::Decimal(n) => Some(n.to_ratio()),
+            Value::Float(_) => None,
+            Value::Integer(n) => Some(BigRational::from_integer(n.clone())),
+            Value::List(_) => None,
+            Value::Quantity(..) => None,
+            Value::Rational(n) => Some(n.clone()),
+        }
+    }
+
+    /// Converts a value to a rational number, including floating point numbers
+    /// by their shortest decimal representation, or None if not finite
+    fn to_decimal_ratio(&self) -> Option<BigRational> {
+        match self {
+            Value::Float(n) if n.is_finite() => Value::parse(&n.to_string())
+                .and_then(|n| n.to_ratio())
+                .or_else(|| BigRational::from_float(*n)),
+            n => n.to_ratio(),
+        }
+    }
+
+    /// Applies a bitwise operation to integer values, or returns NaN otherwise
+    fn bitwise(&self, rhs: &Value, f: fn(&BigInt, &BigInt) -> BigInt) -> Value {
+        match (self.truncate(), rhs.truncate()) {
+            (Some(l), Some(r)) if self.is_integer() && rhs.is_integer() => {
+                Value::Integer(f(&l, &r))
+            }
+            _ => Value::Float(f64::NAN),
+        }
+    }
+
+    /// Truncates a finite real value towards zero, to an integer
+    pub fn truncate(&self) -> Option<BigInt> {
+        match self {
+            Value::Float(n) => BigInt::from_f64(n.trunc()),
+            n => n.to_ratio().map(|n| n.trunc().to_integer()),
+        }
+    }
+
+    /// Wraps an integer around to the range of a fixed-width integer
+    fn wrap(n: &BigInt, bits: u32, signed: bool) -> BigInt {
+        let modulus = BigInt::one() << bits as usize;
+        let n = n.mod_floor(&modulus);
+        match signed && n >= &modulus >> 1 {
+            true => n - modulus,
+            false => n,
+        }
+    }
+
+    /// Converts a ratio with a positive denominator to the nearest f64, by
+    /// scaling the quotient to 63-64 bits and keeping any remainder as a sticky
+    /// bit, such that the final integer conversion rounds correctly.
+    fn ratio_to_f64(numer: &BigInt, denom: &BigInt) -> f64 {
+        if numer.is_zero() {
+            return 0.0;
+        }
+        let shift = 63 - (numer.bits() as i64 - denom.bits() as i64);
+        let (q, r) = match shift {
+            s if s >= 0 => (numer.abs() << s as usize).div_rem(denom),
+            s => numer.abs().div_rem(&(denom << -s as usize)),
+        };
+        let q = q.to_u64().unwrap_or(u64::MAX) | (!r.is_zero() as u64);
+        // Scale in two steps, since 2^-shift may not be representable
+        let n =
+            q as f64 * 2_f64.powi((-shift / 2) as i32) * 2_f64.powi((-shift - -shift / 2) as i32);
+        match numer.is_negative() {
+            true => -n,
+            false => n,
+        }
+    }
+}
@@ -0,0 +1,64 @@
+extern crate rustcalc;
+
+use rustcalc::angle::Angle;
+use rustcalc::environment::Environment;
+use rustcalc::error::EvalError;
+use rustcalc::parser::Parser;
+
+macro_rules! test_angle {
+    ( $( $name:ident: ($angle:expr, $input:expr, $expect:expr), )* ) => {
+    $(
+        #[test]
+        fn $name() {
+            let mut env = Environment::new();
+            env.set_angle($angle);
+            let actual = Parser::new($input).parse().unwrap().evaluate_value(&env);
+            assert_eq!($expect, actual.to_string());
+        }
+    )*
+    }
+}
+
+test_angle! {
+    // Radians
+    radians_sin:            (Angle::Radians,    "sin(pi/6)",        "0.5"),
+    radians_cos:            (Angle::Radians,    "cos(pi/2)",        "0"),
+    radians_tan:            (Angle::Radians,    "tan(pi/4)",        "1"),
+    radians_small:          (Angle::Radians,    "sin(1e-13)",       "0.0000000000001"),
+    radians_asin:           (Angle::Radians,    "asin(1)",          "1.5707963267948966"),
+    radians_degree:         (Angle::Radians,    "sin(30°)",         "0.5"),
+    radians_degree_value:   (Angle::Radians,    "180°",             "3.141592653589793"),
+
+    // Degrees
+    degrees_sin:            (Angle::Degrees,    "sin(30)",          "0.5"),
+    degrees_sin_negative:   (Angle::Degrees,    "sin(-150)",        "-0.5"),
+    degrees_cos:            (Angle::Degrees,    "cos(90)",          "0"),
+    degrees_cos_turns:      (Angle::Degrees,    "cos(3600060)",     "0.5"),
+    degrees_tan:            (Angle::Degrees,    "tan(135)",         "-1"),
+    degrees_tan_pole:       (Angle::Degrees,    "tan(90)",          "inf"),
+    degrees_sec:            (Angle::Degrees,    "sec(60)",          "2"),
+    degrees_csc:            (Angle::Degrees,    "csc(30)",          "2"),
+    degrees_cot:            (Angle::Degrees,    "cot(45)",          "1"),
+    degrees_inexact:        (Angle::Degrees,    "sin(1)",           "0.01745240643728351"),
+    degrees_asin:           (Angle::Degrees,    "asin(0.5)",        "30"),
+    degrees_acos:           (Angle::Degrees,    "acos(-1)",         "180"),
+    degrees_atan:           (Angle::Degrees,    "atan(1)",          "45"),
+    degrees_atan2:          (Angle::Degrees,    "atan2(-1, -1)",    "-135"),
+    degrees_asin_complex:   (Angle::Degrees,    "asin(2)",          "90-75.45612929021688i"),
+    degrees_degree:         (Angle::Degrees,    "sin(30°)",         "0.5"),
+    degrees_hyperbolic:     (Angle::Degrees,    "cosh(0)",          "1"),
+    degrees_radians:        (Angle::Degrees,    "degrees(pi)",      "180"),
+
+    // Gradians
+    gradians_sin:           (Angle::Gradians,   "sin(100)",         "1"),
+    gradians_cos:           (Angle::Gradians,   "cos(200)",         "-1"),
+    gradians_tan:           (Angle::Gradians,   "tan(50)",          "1"),
+    gradians_acos:          (Angle::Gradians,   "acos(0)",          "100"),
+    gradians_degree:        (Angle::Gradians,   "90°",              "100"),
+}
+
+#[test]
+fn degree_unit() {
+    let actual = Parser::new("(30 m)°").parse().unwrap().try_evaluate(&Environment::new());
+    assert_eq!(Err(EvalError::UnexpectedUnit { unit: "m".into() }), actual);
+}
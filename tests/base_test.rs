@@ -0,0 +1,96 @@
+extern crate rustcalc;
+
+use rustcalc::environment::Environment;
+use rustcalc::error::{Error, Expected};
+use rustcalc::lexer::{Span, Token};
+use rustcalc::parser::Parser;
+use rustcalc::value::{Mode, Value};
+
+macro_rules! test_base {
+    ( $( $name:ident: ($input:expr, $expect:expr), )* ) => {
+    $(
+        #[test]
+        fn $name() {
+            let mut env = Environment::new();
+            let statement = Parser::new($input).parse_statement().unwrap();
+            let base = statement.base().unwrap_or(10);
+            let actual = statement.execute(&mut env).unwrap();
+            assert_eq!($expect, actual.to_string_radix(base));
+        }
+    )*
+    }
+}
+
+macro_rules! test_radix {
+    ( $( $name:ident: ($value:expr, $radix:expr, $expect:expr), )* ) => {
+    $(
+        #[test]
+        fn $name() {
+            let value: Value = $value;
+            assert_eq!($expect, value.to_string_radix($radix));
+        }
+    )*
+    }
+}
+
+macro_rules! test_base_error {
+    ( $( $name:ident: ($input:expr, $expect:expr), )* ) => {
+    $(
+        #[test]
+        fn $name() {
+            assert_eq!($expect, Parser::new($input).parse_statement().unwrap_err());
+        }
+    )*
+    }
+}
+
+test_base! {
+    // Conversions
+    binary:                 ("255 to bin",          "0b1111_1111"),
+    binary_grouped:         ("0x1FF to bin",        "0b1_1111_1111"),
+    binary_small:           ("5 to bin",            "0b101"),
+    binary_zero:            ("0 to bin",            "0b0"),
+    decimal:                ("0xFF to dec",         "255"),
+    hex:                    ("255 to hex",          "0xFF"),
+    hex_in:                 ("255 in hex",          "0xFF"),
+    hex_large:              ("1e20 to hex",         "0x56BC75E2D63100000"),
+    hex_negative:           ("-255 to hex",         "-0xFF"),
+    octal:                  ("493 to oct",          "0o755"),
+    base_3:                 ("10 to base 3",        "101"),
+    base_36:                ("35 to base 36",       "Z"),
+    assign:                 ("x = 255 to hex",      "0xFF"),
+    expression:             ("0xF0 | 0x0F to hex",  "0xFF"),
+    last:                   ("255 to hex to bin",   "0b1111_1111"),
+    nested:                 ("(255 to hex) + 1",    "256"),
+
+    // Non-integers
+    fraction:               ("2.5 to bin",          "0b10.1"),
+    fraction_hex:           ("0.75 to hex",         "0x0.C"),
+    fraction_negative:      ("-0.5 to oct",         "-0o0.4"),
+    fraction_limit:         ("0.1 to hex",          "0x0.1999999999999A"),
+    complex:                ("3-4i to bin",         "0b11-0b100i"),
+    quantity:               ("1 km to m to hex",    "0x3E8 m"),
+    infinity:               ("inf to hex",          "inf"),
+}
+
+test_radix! {
+    exact_fraction:         (Value::parse("0.2").unwrap(),      5,  "0.1"),
+    exact_repeating:        (Value::parse("0.1").unwrap(),      3,  "0.0022002200220022002200220022002200"),
+    exact_integer:          (Value::from(-36),                  36, "-10"),
+    float_integer:          (Value::Float(255.0),               16, "0xFF"),
+}
+
+test_base_error! {
+    invalid_base_large:     ("255 to base 37",      Error::InvalidBase { base: "37".into(), span: Span::new(12, 14) }),
+    invalid_base_small:     ("255 to base 1",       Error::InvalidBase { base: "1".into(), span: Span::new(12, 13) }),
+    invalid_base_missing:   ("255 to base x",       Error::UnexpectedToken { found: Token::Ident("x".into()), expected: Expected::Value, span: Span::new(12, 13) }),
+}
+
+#[test]
+fn base_exact_mode() {
+    let mut env = Environment::new();
+    env.set_mode(Mode::Exact);
+    let statement = Parser::new("1/3 to base 3").parse_statement().unwrap();
+    let actual = statement.execute(&mut env).unwrap();
+    assert_eq!("0.1", actual.to_string_radix(statement.base().unwrap()));
+}
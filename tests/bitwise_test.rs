@@ -0,0 +1,89 @@
+extern crate rustcalc;
+
+use rustcalc::environment::Environment;
+use rustcalc::error::EvalError;
+use rustcalc::parser::Parser;
+use rustcalc::value::Mode;
+
+macro_rules! test_bitwise {
+    ( $( $name:ident: ($input:expr, $expect:expr), )* ) => {
+    $(
+        #[test]
+        fn $name() {
+            let actual = Parser::new($input).parse().unwrap().evaluate_value(&Environment::new());
+            assert_eq!($expect, actual.to_string());
+        }
+    )*
+    }
+}
+
+macro_rules! test_fixed {
+    ( $( $name:ident: ($mode:expr, $input:expr, $expect:expr), )* ) => {
+    $(
+        #[test]
+        fn $name() {
+            let (bits, signed, checked) = $mode;
+            let mut env = Environment::new();
+            env.set_mode(Mode::Fixed { bits, signed, checked });
+            let expect: Result<&str, EvalError> = $expect;
+            let actual = Parser::new($input).parse_statement().unwrap().try_execute(&mut env);
+            assert_eq!(expect.map(String::from), actual.map(|v| v.unwrap().to_string()));
+        }
+    )*
+    }
+}
+
+test_bitwise! {
+    // Operators
+    and:                    ("0xFF & 0x0F",         "15"),
+    and_negative:           ("-1 & 0xFF",           "255"),
+    not:                    ("~0",                  "-1"),
+    not_negative:           ("~-6",                 "5"),
+    or:                     ("0xF0 | 0x0F",         "255"),
+    shift_left:             ("1 << 4",              "16"),
+    shift_left_large:       ("1 << 100",            "1267650600228229400000000000000"),
+    shift_right:            ("256 >> 4",            "16"),
+    shift_right_negative:   ("-16 >> 2",            "-4"),
+    shift_right_all:        ("255 >> 100",          "0"),
+    xor:                    ("6 xor 3",             "5"),
+
+    // Precedence
+    precedence_add_shift:   ("1 + 2 << 3",          "24"),
+    precedence_and_or:      ("1 | 2 & 3",           "3"),
+    precedence_and_xor:     ("1 xor 3 & 2",         "3"),
+    precedence_not:         ("~1 + 1",              "-1"),
+    precedence_shift_and:   ("1 << 2 & 4",          "4"),
+    precedence_xor_or:      ("1 xor 1 | 1",         "1"),
+
+    // Invalid operands
+    and_fractional:         ("1.5 & 1",             "NaN"),
+    not_fractional:         ("~0.5",                "NaN"),
+    shift_negative:         ("1 << -1",             "NaN"),
+}
+
+test_fixed! {
+    // Wrapping
+    u8_add:                 ((8, false, false),     "255 + 1",      Ok("0")),
+    u8_literal:             ((8, false, false),     "0x1FF",        Ok("255")),
+    u8_not:                 ((8, false, false),     "~0",           Ok("255")),
+    u8_shift:               ((8, false, false),     "0x81 << 1",    Ok("2")),
+    u8_subtract:            ((8, false, false),     "0 - 1",        Ok("255")),
+    i8_add:                 ((8, true, false),      "127 + 1",      Ok("-128")),
+    i8_not:                 ((8, true, false),      "~0",           Ok("-1")),
+    i16_multiply:           ((16, true, false),     "300 * 300",    Ok("24464")),
+    u32_divide:             ((32, false, false),    "7 / 2",        Ok("3")),
+    i32_divide:             ((32, true, false),     "-7 / 2",       Ok("-3")),
+    u64_max:                ((64, false, false),    "~0",           Ok("18446744073709551615")),
+    i64_min:                ((64, true, false),     "1 << 63",      Ok("-9223372036854775808")),
+    u16_sqrt:               ((16, false, false),    "sqrt(17)",     Ok("4")),
+
+    // Checked
+    u8_checked_add:         ((8, false, true),      "255 + 1",      Err(EvalError::Overflow)),
+    u8_checked_literal:     ((8, false, true),      "256",          Err(EvalError::Overflow)),
+    u8_checked_negate:      ((8, false, true),      "-1",           Err(EvalError::Overflow)),
+    u8_checked_not:         ((8, false, true),      "~0",           Ok("255")),
+    u8_checked_shift:       ((8, false, true),      "0x80 << 1",    Ok("0")),
+    u8_checked_valid:       ((8, false, true),      "200 + 55",     Ok("255")),
+    i8_checked_add:         ((8, true, true),       "127 + 1",      Err(EvalError::Overflow)),
+    i8_checked_min:         ((8, true, true),       "-128",         Err(EvalError::Overflow)),
+}
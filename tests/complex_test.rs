@@ -0,0 +1,111 @@
+extern crate rustcalc;
+
+use rustcalc::environment::Environment;
+use rustcalc::error::EvalError;
+use rustcalc::parser::Parser;
+use rustcalc::value::Mode;
+
+macro_rules! test_complex {
+    ( $( $name:ident: ($input:expr, $expect:expr), )* ) => {
+    $(
+        #[test]
+        fn $name() {
+            let actual = Parser::new($input).parse().unwrap().evaluate_value(&Environment::new());
+            assert_eq!($expect, actual.to_string());
+        }
+    )*
+    }
+}
+
+test_complex! {
+    // Literals and constants
+    constant_i:             ("i",               "i"),
+    constant_i_negative:    ("-i",              "-i"),
+    literal:                ("4i",              "4i"),
+    literal_complex:        ("3+4i",            "3+4i"),
+    literal_complex_neg:    ("3-4i",            "3-4i"),
+    literal_exponent:       ("1.5e3i",          "1500i"),
+    literal_zero:           ("0i",              "0"),
+
+    // Arithmetic
+    add:                    ("(1+2i) + (3-4i)", "4-2i"),
+    add_real:               ("i - i",           "0"),
+    divide:                 ("(1+i) / (1-i)",   "i"),
+    divide_real:            ("(4+2i) / 2",      "2+i"),
+    exponent_i:             ("i ^ 2",           "-1"),
+    exponent_integer:       ("(1+i) ^ 2",       "2i"),
+    exponent_negative:      ("(2i) ^ -1",       "-0.5i"),
+    exponent_real_base:     ("(-4) ^ 0.5",      "0.00000000000000012246467991473532+2i"),
+    exponent_zero_base:     ("0 ^ i",           "0"),
+    multiply:               ("(3+4i) * (3-4i)", "25"),
+    negate:                 ("-(3+4i)",         "-3-4i"),
+    modulo:                 ("5 % i",           "NaN"),
+
+    // Functions
+    abs:                    ("abs(3+4i)",       "5"),
+    abs_real:               ("abs(-5)",         "5"),
+    acosh:                  ("acosh(0)",        "1.5707963267948966i"),
+    arg:                    ("arg(i)",          "1.5707963267948966"),
+    arg_real:               ("arg(-1)",         "3.141592653589793"),
+    asin:                   ("asin(2)",         "1.5707963267948966-1.3169578969248166i"),
+    conj:                   ("conj(3+4i)",      "3-4i"),
+    conj_real:              ("conj(3)",         "3"),
+    cos:                    ("cos(i)",          "1.5430806348152437"),
+    exp:                    ("exp(i * pi / 2)", "0.00000000000000006123233995736766+i"),
+    cosh:                   ("cosh(i * pi)",    "-1"),
+    im:                     ("im(3+4i)",        "4"),
+    im_real:                ("im(3)",           "0"),
+    ln:                     ("ln(i)",           "1.5707963267948966i"),
+    ln_negative:            ("ln(-1)",          "3.141592653589793i"),
+    log10_negative:         ("log10(-100)",     "2+1.3643763538418412i"),
+    nthroot_negative:       ("nthroot(-4, 2)",  "0.00000000000000012246467991473532+2i"),
+    re:                     ("re(3+4i)",        "3"),
+    re_real:                ("re(3)",           "3"),
+    sin:                    ("sin(i)",          "1.1752011936438014i"),
+    sqrt:                   ("sqrt(3+4i)",      "2+i"),
+    sqrt_negative:          ("sqrt(-4)",        "2i"),
+    sqrt_negative_utf8:     ("√-1",             "i"),
+    tan:                    ("tan(i)",          "0.761594155955765i"),
+}
+
+#[test]
+fn evaluate_real() {
+    let env = Environment::new();
+    let evaluate = |input| Parser::new(input).parse().unwrap().evaluate(&env);
+    assert_eq!(25.0, evaluate("(3+4i) * (3-4i)"));
+    assert_eq!(5.0, evaluate("abs(3+4i)"));
+    assert!(evaluate("sqrt(-4)").is_nan());
+}
+
+#[test]
+fn exact() {
+    let mut env = Environment::new();
+    env.set_mode(Mode::Exact);
+    let actual = Parser::new("(1/2 + i) * 2").parse().unwrap().evaluate_value(&env);
+    assert_eq!("1+2i", actual.to_string());
+    let actual = Parser::new("abs(-1/2)").parse().unwrap().evaluate_value(&env);
+    assert_eq!("1/2", actual.to_string());
+}
+
+#[test]
+fn strict() {
+    let env = Environment::new();
+    let evaluate = |input| Parser::new(input).parse().unwrap().try_evaluate(&env);
+    assert_eq!(Ok(-1.0), evaluate("i ^ 2"));
+    assert_eq!(Err(EvalError::NotReal), evaluate("sqrt(-1)"));
+    assert_eq!(Err(EvalError::Undefined), evaluate("5 % i"));
+}
+
+#[test]
+fn strict_statement() {
+    let execute = |input| {
+        let statement = Parser::new(input).parse_statement().unwrap();
+        statement.try_execute(&mut Environment::new()).map(|n| n.unwrap().to_string())
+    };
+    assert_eq!(Err(EvalError::NotReal), execute("sqrt(-4)"));
+    assert_eq!(Err(EvalError::NotReal), execute("ln(-1)"));
+    assert_eq!(Err(EvalError::NotReal), execute("x = (-8) ^ (1 / 3)"));
+    assert_eq!(Ok("1+i".to_string()), execute("sqrt(2i)"));
+    assert_eq!(Ok("2i".to_string()), execute("2 * i"));
+    assert_eq!(Ok("-1".to_string()), execute("i ^ 2"));
+}
@@ -0,0 +1,102 @@
+extern crate rustcalc;
+
+use rustcalc::decimal::Rounding;
+use rustcalc::environment::Environment;
+use rustcalc::error::EvalError;
+use rustcalc::parser::Parser;
+use rustcalc::value::Mode;
+
+macro_rules! test_decimal {
+    ( $( $name:ident: ($input:expr, $expect:expr), )* ) => {
+    $(
+        #[test]
+        fn $name() {
+            let mut env = Environment::new();
+            env.set_mode(Mode::Decimal { precision: 10, rounding: Rounding::HalfUp });
+            let actual = Parser::new($input).parse().unwrap().evaluate_value(&env);
+            assert_eq!($expect, actual.to_string());
+        }
+    )*
+    }
+}
+
+test_decimal! {
+    add:                    ("0.1 + 0.2",               "0.3"),
+    add_rounded:            ("1e9 + 0.4",               "1000000000"),
+    add_rounded_up:         ("1e9 + 0.5",               "1000000001"),
+    divide:                 ("1 / 3",                   "0.3333333333"),
+    divide_negative:        ("-2 / 3",                  "-0.6666666667"),
+    divide_small:           ("1 / 3e12",                "0.0000000000003333333333"),
+    divide_zero:            ("1 / 0",                   "inf"),
+    exponent:               ("2 ^ 30",                  "1073741824"),
+    exponent_negative:      ("2 ^ -3",                  "0.125"),
+    exponent_rounded:       ("2 ^ 40",                  "1099511628000"),
+    factorial:              ("13!",                     "6227020800"),
+    factorial_rounded:      ("20!",                     "2432902008000000000"),
+    integer:                ("42",                      "42"),
+    literal_rounded:        ("3.14159265358979",        "3.141592654"),
+    modulo:                 ("7.5 % 2",                 "1.5"),
+    multiply:               ("1.1 * 1.1",               "1.21"),
+    negate:                 ("-(0.5)",                  "-0.5"),
+    round:                  ("round(1.005, 2)",         "1.01"),
+    round_negative:         ("round(-2.675, 2)",        "-2.68"),
+    round_integer:          ("round(12, 1)",            "12"),
+    function:               ("sqrt(2)",                 "1.4142135623730951"),
+    zero:                   ("0.5 - 0.5",               "0"),
+}
+
+macro_rules! test_rounding {
+    ( $( $name:ident: ($rounding:expr, $input:expr, $expect:expr), )* ) => {
+    $(
+        #[test]
+        fn $name() {
+            let mut env = Environment::new();
+            env.set_mode(Mode::Decimal { precision: 3, rounding: $rounding });
+            let actual = Parser::new($input).parse().unwrap().evaluate_value(&env);
+            assert_eq!($expect, actual.to_string());
+        }
+    )*
+    }
+}
+
+test_rounding! {
+    ceiling:                (Rounding::Ceiling,     "1.231",    "1.24"),
+    ceiling_negative:       (Rounding::Ceiling,     "-1 / 3",   "-0.333"),
+    down:                   (Rounding::Down,        "1.239",    "1.23"),
+    down_negative:          (Rounding::Down,        "-1.239",   "-1.23"),
+    floor:                  (Rounding::Floor,       "1.239",    "1.23"),
+    floor_negative:         (Rounding::Floor,       "-1 / 3",   "-0.334"),
+    half_down:              (Rounding::HalfDown,    "1.235",    "1.23"),
+    half_down_above:        (Rounding::HalfDown,    "1.2351",   "1.24"),
+    half_even:              (Rounding::HalfEven,    "1.235",    "1.24"),
+    half_even_down:         (Rounding::HalfEven,    "1.245",    "1.24"),
+    half_even_sticky:       (Rounding::HalfEven,    "1.24501",  "1.25"),
+    half_up:                (Rounding::HalfUp,      "1.245",    "1.25"),
+    half_up_below:          (Rounding::HalfUp,      "1.2449",   "1.24"),
+    half_up_negative:       (Rounding::HalfUp,      "-1.245",   "-1.25"),
+    half_up_carry:          (Rounding::HalfUp,      "9.995",    "10"),
+    up:                     (Rounding::Up,          "1.231",    "1.24"),
+    up_divide:              (Rounding::Up,          "2 / 3",    "0.667"),
+    up_exact:               (Rounding::Up,          "1.23",     "1.23"),
+}
+
+#[test]
+fn precision_large() {
+    let mut env = Environment::new();
+    env.set_mode(Mode::Decimal { precision: 50, rounding: Rounding::HalfEven });
+    let actual = Parser::new("1 / 7").parse().unwrap().evaluate_value(&env);
+    assert_eq!("0.14285714285714285714285714285714285714285714285714", actual.to_string());
+}
+
+#[test]
+fn strict() {
+    let mut env = Environment::new();
+    env.set_mode(Mode::Decimal { precision: 10, rounding: Rounding::HalfUp });
+    let evaluate = |input| Parser::new(input).parse().unwrap().try_evaluate(&env);
+    assert_eq!(Ok(0.5), evaluate("1 / 2"));
+    assert_eq!(Err(EvalError::DivisionByZero), evaluate("1 / (0.5 - 0.5)"));
+    assert_eq!(
+        Err(EvalError::Domain { function: "round".into(), value: 0.5 }),
+        evaluate("round(1, 0.5)")
+    );
+}
@@ -0,0 +1,106 @@
+extern crate rustcalc;
+
+use std::f64;
+
+use rustcalc::environment::Environment;
+use rustcalc::error::Error;
+use rustcalc::function::Arity;
+use rustcalc::lexer::Span;
+use rustcalc::parser::Parser;
+
+/// Creates an environment with a few registered constants and functions
+fn setup() -> Environment {
+    let mut env = Environment::new();
+    env.register_constant("g", 9.81);
+    env.register_constant("pi", 3.0);
+    env.register_function("clamp", 3, |args| args[0].max(args[1]).min(args[2]));
+    env.register_function("db", 1, |args| 10.0 * args[0].log10());
+    env.register_function("lerp", 3, |args| args[0] + (args[1] - args[0]) * args[2]);
+    env.register_function("log", Arity::range(1, 2), |args| {
+        args[0].ln() / args.get(1).map_or(1.0, |b| b.ln())
+    });
+    env.register_function("sum", Arity::variadic(1), |args| args.iter().sum());
+    env.register_function("two", 0, |_| 2.0);
+    env
+}
+
+macro_rules! test_registry {
+    ( $( $name:ident: ([$( $input:expr ),*], $expect:expr), )* ) => {
+    $(
+        #[test]
+        fn $name() {
+            let expect: Result<f64, Error> = $expect;
+            let mut env = setup();
+            let mut actual = Ok(None);
+            for input in &[$( $input ),*] {
+                actual = Parser::with_environment(input, &env)
+                    .parse_statement()
+                    .map(|statement| statement.execute(&mut env).map(f64::from));
+                if actual.is_err() {
+                    break;
+                }
+            }
+            match expect {
+                Ok(v) if v.is_nan() => assert!(actual.unwrap().unwrap().is_nan(), "Expected NaN"),
+                _ => assert_eq!(expect.map(Some), actual),
+            }
+        }
+    )*
+    }
+}
+
+test_registry! {
+    constant:               (["g"],                     Ok(9.81)),
+    constant_expression:    (["2 * g"],                 Ok(19.62)),
+    constant_case:          (["G"],                     Err(Error::UnknownConstant { name: "G".into(), span: Span::new(0, 1) })),
+    constant_builtin:       (["pi"],                    Ok(f64::consts::PI)),
+    constant_assign:        (["g = 1"],                 Err(Error::AssignConstant { name: "g".into(), span: Span::new(0, 1) })),
+    constant_param:         (["f(g) = g"],              Err(Error::InvalidParameter { name: "g".into(), span: Span::new(2, 3) })),
+    constant_in_function:   (["f(m) = m * g", "f(2)"],  Ok(19.62)),
+
+    function:               (["clamp(5, 0, 3)"],        Ok(3.0)),
+    function_low:           (["clamp(-1, 0, 3)"],       Ok(0.0)),
+    function_nested:        (["lerp(0, 10, clamp(2, 0, 0.5))"], Ok(5.0)),
+    function_expression:    (["db(100) + 1"],           Ok(21.0)),
+    function_nan:           (["db(-1)"],                Ok(f64::NAN)),
+    function_noargs:        (["two() ^ 3"],             Ok(8.0)),
+    function_case:          (["CLAMP(1, 2, 3)"],        Err(Error::UnknownFunction { name: "CLAMP".into(), span: Span::new(0, 5) })),
+    function_missing:       (["clamp(1, 2)"],           Err(Error::ArityMismatch { name: "clamp".into(), expected: Arity::exact(3), got: 2, span: Span::new(0, 11) })),
+    function_extra:         (["clamp(1, 2, 3, 4)"],     Err(Error::ArityMismatch { name: "clamp".into(), expected: Arity::exact(3), got: 4, span: Span::new(0, 17) })),
+    function_range_min:     (["log(100)"],              Ok(100_f64.ln())),
+    function_range_max:     (["log(8, 2)"],             Ok(3.0)),
+    function_range_missing: (["log()"],                 Err(Error::ArityMismatch { name: "log".into(), expected: Arity::range(1, 2), got: 0, span: Span::new(0, 5) })),
+    function_range_extra:   (["log(8, 2, 1)"],          Err(Error::ArityMismatch { name: "log".into(), expected: Arity::range(1, 2), got: 3, span: Span::new(0, 12) })),
+    function_variadic:      (["sum(1, 2, 3, 4)"],       Ok(10.0)),
+    function_variadic_one:  (["sum(1)"],                Ok(1.0)),
+    function_variadic_none: (["sum()"],                 Err(Error::ArityMismatch { name: "sum".into(), expected: Arity::variadic(1), got: 0, span: Span::new(0, 5) })),
+    function_builtin:       (["sqrt(4)"],               Ok(2.0)),
+    function_in_function:   (["f(x) = db(x) * 2", "f(10)"], Ok(20.0)),
+    function_redefine:      (["clamp(x) = x"],          Err(Error::RedefineFunction { name: "clamp".into(), span: Span::new(0, 5) })),
+}
+
+#[test]
+fn register_closure() {
+    let mut env = Environment::new();
+    let scale = 3.0;
+    env.register_function("scale", 1, move |args| args[0] * scale);
+    let expr = Parser::with_environment("scale(2) + 1", &env).parse().unwrap();
+    assert_eq!(expr.evaluate(&env), 7.0);
+}
+
+#[test]
+fn register_replace() {
+    let mut env = Environment::new();
+    env.register_constant("k", 1.0);
+    env.register_function("f", 1, |args| args[0]);
+    env.register_constant("k", 2.0);
+    env.register_function("f", 1, |args| args[0] * 10.0);
+    let expr = Parser::with_environment("f(k)", &env).parse().unwrap();
+    assert_eq!(expr.evaluate(&env), 20.0);
+}
+
+#[test]
+fn register_without_environment() {
+    let result = Parser::new("g").parse().map(|expr| expr.evaluate(&setup()));
+    assert_eq!(result, Err(Error::UnknownConstant { name: "g".into(), span: Span::new(0, 1) }));
+}
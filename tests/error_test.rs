@@ -0,0 +1,39 @@
+extern crate rustcalc;
+
+use rustcalc::environment::Environment;
+use rustcalc::lexer::Span;
+use rustcalc::parser::Parser;
+
+macro_rules! test_message {
+    ( $( $name:ident: ($input:expr, $expect:expr, $span:expr), )* ) => {
+    $(
+        #[test]
+        fn $name() {
+            let mut env = Environment::new();
+            env.register_constant("g", 9.81);
+            env.register_function("clamp", 3, |args| args[0].max(args[1]).min(args[2]));
+            let err = Parser::with_environment($input, &env).parse_statement().unwrap_err();
+            assert_eq!(err.to_string(), $expect);
+            assert_eq!(err.span(), Some($span));
+        }
+    )*
+    }
+}
+
+test_message! {
+    arity_missing:          ("sqrt()",            "Missing argument for sqrt()",     Span::new(0, 6)),
+    arity_unexpected:       ("clamp(1, 2, 3, 4)", "Unexpected argument for clamp()", Span::new(0, 17)),
+    assign_constant:        ("g = 1",             "Cannot assign to constant g",     Span::new(0, 1)),
+    duplicate_parameter:    ("f(x, x) = x",       "Duplicate parameter x",           Span::new(5, 6)),
+    invalid_digit:          ("0b1012",            "Invalid digit 2 for base 2",      Span::new(5, 6)),
+    invalid_number:         ("1 + 2e",            "Invalid number 2e",               Span::new(4, 6)),
+    invalid_parameter:      ("f(pi) = 1",         "Invalid parameter pi",            Span::new(2, 4)),
+    redefine_function:      ("clamp(x) = x",      "Cannot redefine function clamp",  Span::new(0, 5)),
+    unexpected_character:   ("1 + $",             "Unexpected character $",          Span::new(4, 5)),
+    unexpected_end:         ("1 +",               "Unexpected end of input",         Span::new(3, 3)),
+    unexpected_token_end:   ("1 2",               "Unexpected token 2",              Span::new(2, 3)),
+    unexpected_token_token: ("sqrt(1 2)",         "Expected token ,, found 2",       Span::new(7, 8)),
+    unexpected_token_value: ("1 + *",             "Expected value, found *",         Span::new(4, 5)),
+    unknown_constant:       ("2 * x",             "Unknown constant x",              Span::new(4, 5)),
+    unknown_function:       ("f(1, 2)",           "Unknown function f",              Span::new(0, 1)),
+}
@@ -2,7 +2,10 @@ extern crate rustcalc;
 
 use std::f64;
 
-use rustcalc::error::Error;
+use rustcalc::environment::Environment;
+use rustcalc::error::{Error, Expected};
+use rustcalc::function::Arity;
+use rustcalc::lexer::{Span, Token};
 use rustcalc::parser::Parser;
 
 macro_rules! test_evaluate {
@@ -11,7 +14,7 @@ macro_rules! test_evaluate {
         #[test]
         fn $name() {
             let expect: Result<f64, Error> = $expect;
-            let actual = Parser::new($input).parse().map(|expr| expr.evaluate());
+            let actual = Parser::new($input).parse().map(|expr| expr.evaluate(&Environment::new()));
             match expect {
                 Ok(v) if v.is_nan() => assert!(actual.unwrap().is_nan(), "Expected NaN"),
                 _ => assert_eq!(expect, actual),
@@ -22,7 +25,7 @@ macro_rules! test_evaluate {
 }
 
 test_evaluate! {
-    empty:                  ("",            Err(Error::Parse("Unexpected end of input".into()))),
+    empty:                  ("",            Err(Error::UnexpectedEnd { span: Span::new(0, 0) })),
 
     // Literals
     constant_e:             ("e",           Ok(f64::consts::E)),
@@ -33,33 +36,50 @@ test_evaluate! {
     constant_inf:           ("inf",         Ok(f64::INFINITY)),
     constant_nan:           ("nan",         Ok(f64::NAN)),
     constant_nan_mixedcase: ("NaN",         Ok(f64::NAN)),
-    constant_unknown:       ("x",           Err(Error::Parse("Unknown constant x".into()))),
-    constant_unknown_full:  ("a_LoNg_1",    Err(Error::Parse("Unknown constant a_LoNg_1".into()))),
-    constant_unknown_hyphen:("a-constant",  Err(Error::Parse("Unknown constant a".into()))),
-    constant_unknown_utf8:  ("銹",          Err(Error::Parse("Unknown constant 銹".into()))),
-    constant_unknown_emoji: ("👋",          Err(Error::Parse("Unexpected character 👋".into()))),
-    constant_unknown_num:   ("1pi",         Err(Error::Parse("Unexpected token pi".into()))),
+    constant_unknown:       ("x",           Err(Error::UnknownConstant { name: "x".into(), span: Span::new(0, 1) })),
+    constant_unknown_full:  ("a_LoNg_1",    Err(Error::UnknownConstant { name: "a_LoNg_1".into(), span: Span::new(0, 8) })),
+    constant_unknown_hyphen:("a-constant",  Err(Error::UnknownConstant { name: "a".into(), span: Span::new(0, 1) })),
+    constant_unknown_utf8:  ("銹",          Err(Error::UnknownConstant { name: "銹".into(), span: Span::new(0, 1) })),
+    constant_unknown_span:  ("π + √x",      Err(Error::UnknownConstant { name: "x".into(), span: Span::new(5, 6) })),
+    constant_unknown_emoji: ("👋",          Err(Error::UnexpectedCharacter { found: '👋', span: Span::new(0, 1) })),
+    constant_unknown_num:   ("1pi",         Err(Error::UnexpectedToken { found: Token::Ident("pi".into()), expected: Expected::End, span: Span::new(1, 3) })),
 
     number:                 ("1",           Ok(1.0)),
     number_decimal:         ("3.14",        Ok(3.14)),
     number_decimal_nodec:   ("3.",          Ok(3.0)),
-    number_decimal_comma:   ("3,14",        Err(Error::Parse("Unexpected token ,".into()))),
-    number_decimal_multi:   ("3.14.15",     Err(Error::Parse("Unexpected character .".into()))),
-    number_repeated:        ("1 2",         Err(Error::Parse("Unexpected token 2".into()))),
+    number_decimal_comma:   ("3,14",        Err(Error::UnexpectedToken { found: Token::Comma, expected: Expected::End, span: Span::new(1, 2) })),
+    number_decimal_multi:   ("3.14.15",     Err(Error::UnexpectedCharacter { found: '.', span: Span::new(4, 5) })),
+    number_repeated:        ("1 2",         Err(Error::UnexpectedToken { found: Token::Number("2".into()), expected: Expected::End, span: Span::new(2, 3) })),
     number_sci:             ("3e2",         Ok(300.0)),
     number_sci_zero:        ("3e0",         Ok(3.0)),
     number_sci_capital:     ("3E2",         Ok(300.0)),
     number_sci_dec_base:    ("3.14e1",      Ok(31.4)),
-    number_sci_dec_exp:     ("3e2.1",       Err(Error::Parse("Unexpected character .".into()))),
+    number_sci_dec_exp:     ("3e2.1",       Err(Error::UnexpectedCharacter { found: '.', span: Span::new(3, 4) })),
     number_sci_neg_base:    ("-3.14e1",     Ok(-31.4)),
     number_sci_neg_exp:     ("3.14e-2",     Ok(0.0314)),
-    number_sci_no_exp:      ("3e",          Err(Error::Parse("invalid float literal".into()))),
+    number_sci_no_exp:      ("3e",          Err(Error::InvalidNumber { literal: "3e".into(), span: Span::new(0, 2) })),
     number_sci_exp_plus:    ("3.14e+2",     Ok(314.0)),
-    number_sci_exp_signs:   ("3.14e--2",    Err(Error::Parse("invalid float literal".into()))),
+    number_sci_exp_signs:   ("3.14e--2",    Err(Error::InvalidNumber { literal: "3.14e-".into(), span: Span::new(0, 6) })),
+    number_separator:       ("1_000_000",   Ok(1000000.0)),
+    number_separator_dec:   ("3.141_5e1_0", Ok(31415000000.0)),
+    number_separator_end:   ("1_",          Err(Error::UnexpectedCharacter { found: '_', span: Span::new(1, 2) })),
+    number_separator_twice: ("1__0",        Err(Error::UnexpectedCharacter { found: '_', span: Span::new(1, 2) })),
+    number_hex:             ("0xFF",        Ok(255.0)),
+    number_hex_lowercase:   ("0xff",        Ok(255.0)),
+    number_hex_capital:     ("0XfF",        Ok(255.0)),
+    number_hex_separator:   ("0xFF_FF",     Ok(65535.0)),
+    number_hex_invalid:     ("0xFG",        Err(Error::InvalidDigit { digit: 'G', radix: 16, span: Span::new(3, 4) })),
+    number_hex_empty:       ("0x",          Err(Error::InvalidNumber { literal: "0x".into(), span: Span::new(0, 2) })),
+    number_octal:           ("0o755",       Ok(493.0)),
+    number_octal_invalid:   ("0o78",        Err(Error::InvalidDigit { digit: '8', radix: 8, span: Span::new(3, 4) })),
+    number_binary:          ("0b1010",      Ok(10.0)),
+    number_binary_neg:      ("-0b1010",     Ok(-10.0)),
+    number_binary_sep:      ("0b1010_0101", Ok(165.0)),
+    number_binary_invalid:  ("0b102",       Err(Error::InvalidDigit { digit: '2', radix: 2, span: Span::new(4, 5) })),
 
     // Prefix operators
-    prefix_bare:            ("+",           Err(Error::Parse("Unexpected end of input".into()))),
-    prefix_bare_multi:      ("-+",          Err(Error::Parse("Unexpected end of input".into()))),
+    prefix_bare:            ("+",           Err(Error::UnexpectedEnd { span: Span::new(1, 1) })),
+    prefix_bare_multi:      ("-+",          Err(Error::UnexpectedEnd { span: Span::new(2, 2) })),
     prefix_multi:           ("-+-+-1",      Ok(-1.0)),
 
     identity:               ("+1",          Ok(1.0)),
@@ -82,9 +102,16 @@ test_evaluate! {
 
     // Postfix operators
     factorial:              ("5!",          Ok(120.0)),
-    factorial_multi:        ("3!!",         Ok(720.0)),
+    factorial_multi:        ("(3!)!",       Ok(720.0)),
+    double_factorial:       ("7!!",         Ok(105.0)),
+    double_factorial_even:  ("8!!",         Ok(384.0)),
+    double_factorial_zero:  ("0!!",         Ok(1.0)),
     factorial_zero:         ("0!",          Ok(1.0)),
-    factorial_decimal:      ("3.14!",       Ok(f64::NAN)),
+    factorial_decimal:      ("3.14!",       Ok(7.173269190187903)),
+    factorial_half:         ("0.5!",        Ok(0.8862269254527586)),
+    factorial_neg_fraction: ("(-0.5)!",     Ok(1.7724538509055159)),
+    factorial_overflow:     ("171!",        Ok(f64::INFINITY)),
+    factorial_huge:         ("1e300!",      Ok(f64::INFINITY)),
     factorial_negative:     ("-1!",         Ok(f64::NAN)),
     factorial_precedence:   ("2 ^ 3!",      Ok(64.0)),
     factorial_infinity:     ("inf!",        Ok(f64::INFINITY)),
@@ -92,10 +119,10 @@ test_evaluate! {
     factorial_nan:          ("nan!",        Ok(f64::NAN)),
 
     // Infix operators
-    infix_multi:            ("1 * / 2",     Err(Error::Parse("Expected value, found /".into()))),
-    infix_bare:             ("*",           Err(Error::Parse("Expected value, found *".into()))),
-    infix_pre:              ("* 2",         Err(Error::Parse("Expected value, found *".into()))),
-    infix_post:             ("2 *",         Err(Error::Parse("Unexpected end of input".into()))),
+    infix_multi:            ("1 * / 2",     Err(Error::UnexpectedToken { found: Token::Slash, expected: Expected::Value, span: Span::new(4, 5) })),
+    infix_bare:             ("*",           Err(Error::UnexpectedToken { found: Token::Asterisk, expected: Expected::Value, span: Span::new(0, 1) })),
+    infix_pre:              ("* 2",         Err(Error::UnexpectedToken { found: Token::Asterisk, expected: Expected::Value, span: Span::new(0, 1) })),
+    infix_post:             ("2 *",         Err(Error::UnexpectedEnd { span: Span::new(3, 3) })),
 
     add:                    ("1 + 2",       Ok(3.0)),
     add_decimals:           ("3.1 + 3.3",   Ok(6.4)),
@@ -215,23 +242,51 @@ test_evaluate! {
 
     // Parenthesis
     paren_precedence:       ("(2 + 3)!",    Ok(120.0)),
-    paren_noclose:          ("(1 + 2",      Err(Error::Parse("Unexpected end of input".into()))),
-    paren_noopen:           ("1 + 2 )",     Err(Error::Parse("Unexpected token )".into()))),
+    paren_noclose:          ("(1 + 2",      Err(Error::UnexpectedEnd { span: Span::new(6, 6) })),
+    paren_noopen:           ("1 + 2 )",     Err(Error::UnexpectedToken { found: Token::CloseParen, expected: Expected::End, span: Span::new(6, 7) })),
 
     // Functions
     func_args:              ("sqrt(1)",                 Ok(1.0)),
-    func_args_comma:        ("sqrt(,)",                 Err(Error::Parse("Expected value, found ,".into()))),
-    func_args_trail_comma:  ("sqrt(1,)",                Err(Error::Parse("Expected value, found )".into()))),
-    func_args_missing:      ("sqrt()",                  Err(Error::Parse("Missing argument for sqrt()".into()))),
-    func_args_many:         ("sqrt(1, 2)",              Err(Error::Parse("Unexpected argument for sqrt()".into()))),
-    func_args_tight:        ("sqrt(1,2)",               Err(Error::Parse("Unexpected argument for sqrt()".into()))),
-    func_varargs_missing:   ("round()",                 Err(Error::Parse("Missing argument for round()".into()))),
+    func_args_comma:        ("sqrt(,)",                 Err(Error::UnexpectedToken { found: Token::Comma, expected: Expected::Value, span: Span::new(5, 6) })),
+    func_args_trail_comma:  ("sqrt(1,)",                Err(Error::UnexpectedToken { found: Token::CloseParen, expected: Expected::Value, span: Span::new(7, 8) })),
+    func_args_missing:      ("sqrt()",                  Err(Error::ArityMismatch { name: "sqrt".into(), expected: Arity::exact(1), got: 0, span: Span::new(0, 6) })),
+    func_args_many:         ("sqrt(1, 2)",              Err(Error::ArityMismatch { name: "sqrt".into(), expected: Arity::exact(1), got: 2, span: Span::new(0, 10) })),
+    func_args_tight:        ("sqrt(1,2)",               Err(Error::ArityMismatch { name: "sqrt".into(), expected: Arity::exact(1), got: 2, span: Span::new(0, 9) })),
+    func_varargs_missing:   ("round()",                 Err(Error::ArityMismatch { name: "round".into(), expected: Arity::range(1, 2), got: 0, span: Span::new(0, 7) })),
     func_varargs_1:         ("round(1)",                Ok(1.0)),
     func_varargs_2:         ("round(1, 2)",             Ok(1.0)),
-    func_varargs_many:      ("round(1, 2, 3)",          Err(Error::Parse("Unexpected argument for round()".into()))),
+    func_varargs_many:      ("round(1, 2, 3)",          Err(Error::ArityMismatch { name: "round".into(), expected: Arity::range(1, 2), got: 3, span: Span::new(0, 14) })),
     func_space:             ("sqrt (1)",                Ok(1.0)),
-    func_missing_close:     ("sqrt (1",                 Err(Error::Parse("Unexpected end of input".into()))),
-    func_no_parens:         ("sqrt 1",                  Err(Error::Parse("Unknown constant sqrt".into()))),
+    func_missing_close:     ("sqrt (1",                 Err(Error::UnexpectedEnd { span: Span::new(7, 7) })),
+    func_no_parens:         ("sqrt 1",                  Err(Error::UnknownConstant { name: "sqrt".into(), span: Span::new(0, 4) })),
+
+    acos:                   ("acos(-1)",                Ok(f64::consts::PI)),
+    acos_zero:              ("acos(0)",                 Ok(f64::consts::FRAC_PI_2)),
+    acos_nan:               ("acos(nan)",               Ok(f64::NAN)),
+    acosh:                  ("acosh(1)",                Ok(0.0)),
+    acosh_inf:              ("acosh(inf)",              Ok(f64::INFINITY)),
+    asin:                   ("asin(1)",                 Ok(f64::consts::FRAC_PI_2)),
+    asin_zero:              ("asin(0)",                 Ok(0.0)),
+    asin_inverse:           ("sin(asin(0.5))",          Ok(0.5)),
+    asin_inf:               ("asin(inf)",               Ok(f64::NAN)),
+    asinh:                  ("asinh(0)",                Ok(0.0)),
+    asinh_inverse:          ("asinh(sinh(2))",          Ok(2.0)),
+    atan:                   ("atan(1)",                 Ok(f64::consts::FRAC_PI_4)),
+    atan_inf:               ("atan(inf)",               Ok(f64::consts::FRAC_PI_2)),
+    atan_neginf:            ("atan(-inf)",              Ok(-f64::consts::FRAC_PI_2)),
+    atan2:                  ("atan2(1, 1)",             Ok(f64::consts::FRAC_PI_4)),
+    atan2_quadrant:         ("atan2(1, -1)",            Ok(3.0 * f64::consts::FRAC_PI_4)),
+    atan2_negative:         ("atan2(-1, 0)",            Ok(-f64::consts::FRAC_PI_2)),
+    atan2_zero:             ("atan2(0, 0)",             Ok(0.0)),
+    atan2_nan:              ("atan2(nan, 1)",           Ok(f64::NAN)),
+    atanh:                  ("atanh(0)",                Ok(0.0)),
+    atanh_one:              ("atanh(1)",                Ok(f64::INFINITY)),
+
+    cbrt:                   ("cbrt(27)",                Ok(3.0)),
+    cbrt_exact:             ("cbrt(1000)",              Ok(10.0)),
+    cbrt_negative:          ("cbrt(-8)",                Ok(-2.0)),
+    cbrt_inf:               ("cbrt(-inf)",              Ok(f64::NEG_INFINITY)),
+    cbrt_nan:               ("cbrt(nan)",               Ok(f64::NAN)),
 
     cos_zero:               ("round(cos(0), 2)",        Ok(1.0)),
     cos_1_2pi:              ("round(cos(1/2*pi), 2)",   Ok(0.0)),
@@ -245,6 +300,13 @@ test_evaluate! {
     cos_neginf:             ("cos(-inf)",               Ok(f64::NAN)),
     cos_nan:                ("cos(nan)",                Ok(f64::NAN)),
 
+    cosh:                   ("cosh(0)",                 Ok(1.0)),
+    cosh_inf:               ("cosh(-inf)",              Ok(f64::INFINITY)),
+    cot:                    ("round(cot(pi/4), 2)",     Ok(1.0)),
+    cot_zero:               ("cot(0)",                  Ok(f64::INFINITY)),
+    csc:                    ("csc(pi/2)",               Ok(1.0)),
+    csc_nan:                ("csc(nan)",                Ok(f64::NAN)),
+
     degrees:                ("degrees(pi)",             Ok(180.0)),
     degrees_2pi:            ("degrees(2*pi)",           Ok(360.0)),
     degrees_4pi:            ("degrees(4*pi)",           Ok(720.0)),
@@ -254,6 +316,54 @@ test_evaluate! {
     degrees_neginf:         ("degrees(-inf)",           Ok(f64::NEG_INFINITY)),
     degrees_nan:            ("degrees(nan)",            Ok(f64::NAN)),
 
+    exp_func:               ("exp(1)",                  Ok(f64::consts::E)),
+    exp_func_zero:          ("exp(0)",                  Ok(1.0)),
+    exp_func_negative:      ("exp(-inf)",               Ok(0.0)),
+    exp_func_nan:           ("exp(nan)",                Ok(f64::NAN)),
+    expm1:                  ("expm1(1e-10)",            Ok(1.00000000005e-10)),
+    expm1_zero:             ("expm1(0)",                Ok(0.0)),
+
+    gamma:                  ("gamma(5)",                Ok(24.0)),
+    gamma_half:             ("gamma(0.5) ^ 2",          Ok(3.1415926535897927)),
+    gamma_negative:         ("gamma(-1.5)",             Ok(2.363271801207352)),
+    gamma_large:            ("gamma(171.5)",            Ok(9.483367566823837e307)),
+    gamma_overflow:         ("gamma(172)",              Ok(f64::INFINITY)),
+    gamma_zero:             ("gamma(0)",                Ok(f64::NAN)),
+    gamma_pole:             ("gamma(-2)",               Ok(f64::NAN)),
+    gamma_inf:              ("gamma(inf)",              Ok(f64::INFINITY)),
+    lgamma:                 ("lgamma(3)",               Ok(f64::consts::LN_2)),
+    lgamma_one:             ("lgamma(1)",               Ok(0.0)),
+    lgamma_large:           ("lgamma(1e6)",             Ok(12815504.569147611)),
+    lgamma_negative:        ("lgamma(-0.5)",            Ok(1.2655121234846447)),
+    lgamma_pole:            ("lgamma(0)",               Ok(f64::NAN)),
+
+    hypot:                  ("hypot(3, 4)",             Ok(5.0)),
+    hypot_negative:         ("hypot(-5, 12)",           Ok(13.0)),
+    hypot_inf:              ("hypot(nan, inf)",         Ok(f64::INFINITY)),
+
+    ln:                     ("ln(e)",                   Ok(1.0)),
+    ln_one:                 ("ln(1)",                   Ok(0.0)),
+    ln_zero:                ("ln(0)",                   Ok(f64::NEG_INFINITY)),
+    ln_inf:                 ("ln(inf)",                 Ok(f64::INFINITY)),
+    ln_nan:                 ("ln(nan)",                 Ok(f64::NAN)),
+    log:                    ("log(81, 3)",              Ok(4.0)),
+    log_2:                  ("log(8, 2)",               Ok(3.0)),
+    log_10:                 ("log(1000, 10)",           Ok(3.0)),
+    log_fraction:           ("log(0.25, 0.5)",          Ok(2.0)),
+    log_args_missing:       ("log(100)",                Err(Error::ArityMismatch { name: "log".into(), expected: Arity::exact(2), got: 1, span: Span::new(0, 8) })),
+    log10:                  ("log10(1000)",             Ok(3.0)),
+    log10_fraction:         ("log10(0.001)",            Ok(-3.0)),
+    log1p:                  ("log1p(1e-10)",            Ok(9.999999999500001e-11)),
+    log1p_minus_one:        ("log1p(-1)",               Ok(f64::NEG_INFINITY)),
+    log2:                   ("log2(1024)",              Ok(10.0)),
+    log2_fraction:          ("log2(0.5)",               Ok(-1.0)),
+
+    nthroot:                ("nthroot(16, 4)",          Ok(2.0)),
+    nthroot_exact:          ("nthroot(1000, 3)",        Ok(10.0)),
+    nthroot_negative:       ("nthroot(-32, 5)",         Ok(-2.0)),
+    nthroot_fraction:       ("nthroot(2, 0.5)",         Ok(4.0)),
+    nthroot_inexact:        ("nthroot(2, 2)",           Ok(f64::consts::SQRT_2)),
+
     radians:                ("radians(180)",            Ok(f64::consts::PI)),
     radians_360:            ("radians(360)",            Ok(2.0 * f64::consts::PI)),
     radians_720:            ("radians(720)",            Ok(4.0 * f64::consts::PI)),
@@ -263,6 +373,10 @@ test_evaluate! {
     radians_neginf:         ("radians(-inf)",           Ok(f64::NEG_INFINITY)),
     radians_nan:            ("radians(nan)",            Ok(f64::NAN)),
 
+    sec:                    ("sec(0)",                  Ok(1.0)),
+    sec_pi:                 ("sec(pi)",                 Ok(-1.0)),
+    sec_inf:                ("sec(inf)",                Ok(f64::NAN)),
+
     sin_zero:               ("round(sin(0), 2)",        Ok(0.0)),
     sin_1_2pi:              ("round(sin(1/2*pi), 2)",   Ok(1.0)),
     sin_pi:                 ("round(sin(pi), 2)",       Ok(0.0)),
@@ -275,6 +389,9 @@ test_evaluate! {
     sin_neginf:             ("sin(-inf)",               Ok(f64::NAN)),
     sin_nan:                ("sin(nan)",                Ok(f64::NAN)),
 
+    sinh:                   ("sinh(0)",                 Ok(0.0)),
+    sinh_inf:               ("sinh(-inf)",              Ok(f64::NEG_INFINITY)),
+
     round:                  ("round(3.14)",             Ok(3.0)),
     round_0_5:              ("round(0.5)",              Ok(1.0)),
     round_minus_0_5:        ("round(-0.5)",             Ok(-1.0)),
@@ -290,6 +407,60 @@ test_evaluate! {
     round_inf_inf:          ("round(inf, inf)",         Ok(f64::NAN)),
     round_neginf:           ("round(-inf)",             Ok(f64::NEG_INFINITY)),
     round_nan:              ("round(nan)",              Ok(f64::NAN)),
+    round_decimal:          ("round(2.675, 2)",         Ok(2.68)),
+    round_half_away:        ("round(-2.5, 0, half_away)", Ok(-3.0)),
+    round_half_even:        ("round(2.5, 0, half_even)", Ok(2.0)),
+    round_half_even_odd:    ("round(3.5, half_even)",   Ok(4.0)),
+    round_toward_zero:      ("round(-2.78, 1, toward_zero)", Ok(-2.7)),
+    round_mode_unknown:     ("round(2.5, 0, half_odd)", Err(Error::UnknownConstant { name: "half_odd".into(), span: Span::new(14, 22) })),
+    round_mode_expression:  ("round(2.5, 0, 1)",        Err(Error::ArityMismatch { name: "round".into(), expected: Arity::range(1, 2), got: 3, span: Span::new(0, 16) })),
+
+    roundto:                ("roundto(17, 5)",          Ok(15.0)),
+    roundto_decimal:        ("roundto(1.234, 0.05)",    Ok(1.25)),
+    roundto_negative_step:  ("roundto(17, -5)",         Ok(15.0)),
+    roundto_toward_zero:    ("roundto(7.9, 0.5, toward_zero)", Ok(7.5)),
+    roundto_zero:           ("roundto(17, 0)",          Ok(f64::NAN)),
+
+    floor:                  ("floor(2.7)",              Ok(2.0)),
+    floor_negative:         ("floor(-2.5)",             Ok(-3.0)),
+    floor_inf:              ("floor(-inf)",             Ok(f64::NEG_INFINITY)),
+    ceil:                   ("ceil(2.1)",               Ok(3.0)),
+    ceil_negative:          ("ceil(-2.5)",              Ok(-2.0)),
+    trunc:                  ("trunc(2.7)",              Ok(2.0)),
+    trunc_negative:         ("trunc(-2.7)",             Ok(-2.0)),
+    frac:                   ("frac(2.75)",              Ok(0.75)),
+    frac_negative:          ("frac(-2.5)",              Ok(-0.5)),
+    sign:                   ("sign(-3.5)",              Ok(-1.0)),
+    sign_zero:              ("sign(0)",                 Ok(0.0)),
+    sign_positive:          ("sign(2)",                 Ok(1.0)),
+    sign_nan:               ("sign(nan)",               Ok(f64::NAN)),
+
+    sum:                    ("sum(1, 2, 3.5)",          Ok(6.5)),
+    sum_one:                ("sum(4)",                  Ok(4.0)),
+    sum_missing:            ("sum()",                   Err(Error::ArityMismatch { name: "sum".into(), expected: Arity::variadic(1), got: 0, span: Span::new(0, 5) })),
+    sum_inf:                ("sum(inf, -inf)",          Ok(f64::NAN)),
+    product:                ("product(2, 3, 4)",        Ok(24.0)),
+    min:                    ("min(3, -1, 2)",           Ok(-1.0)),
+    min_nan:                ("min(1, nan)",             Ok(f64::NAN)),
+    min_complex:            ("min(1, i)",               Ok(f64::NAN)),
+    max:                    ("max(3, -1, 2)",           Ok(3.0)),
+    max_inf:                ("max(1, inf)",             Ok(f64::INFINITY)),
+    mean:                   ("mean(1, 2, 3, 4)",        Ok(2.5)),
+    median_odd:             ("median(5, 1, 3)",         Ok(3.0)),
+    median_even:            ("median(5, 1, 3, 2)",      Ok(2.5)),
+    mode:                   ("mode(1, 3, 3, 2)",        Ok(3.0)),
+    mode_tie:               ("mode(3, 2, 3, 2, 1)",     Ok(2.0)),
+    variance:               ("variance(1, 2, 3, 4)",    Ok(1.6666666666666667)),
+    variance_one:           ("variance(1)",             Ok(f64::NAN)),
+    pvariance:              ("pvariance(1, 2, 3, 4)",   Ok(1.25)),
+    stdev:                  ("stdev(2, 4, 4, 4, 5, 5, 7, 9)", Ok(2.138089935299395)),
+    pstdev:                 ("pstdev(2, 4, 4, 4, 5, 5, 7, 9)", Ok(2.0)),
+    percentile:             ("percentile(25, 1, 2, 3, 4, 5)", Ok(2.0)),
+    percentile_interpolate: ("percentile(90, 10, 20)",  Ok(19.0)),
+    percentile_zero:        ("percentile(0, 3, 1, 2)",  Ok(1.0)),
+    percentile_hundred:     ("percentile(100, 3, 1, 2)", Ok(3.0)),
+    percentile_range:       ("percentile(101, 1, 2)",   Ok(f64::NAN)),
+    percentile_missing:     ("percentile(50)",          Err(Error::ArityMismatch { name: "percentile".into(), expected: Arity::variadic(2), got: 1, span: Span::new(0, 14) })),
 
     sqrt_function:          ("sqrt(4)",             Ok(2.0)),
 
@@ -303,4 +474,8 @@ test_evaluate! {
     tan_inf:                ("tan(inf)",                Ok(f64::NAN)),
     tan_neginf:             ("tan(-inf)",               Ok(f64::NAN)),
     tan_nan:                ("tan(nan)",                Ok(f64::NAN)),
+
+    tanh:                   ("tanh(0)",                 Ok(0.0)),
+    tanh_inf:               ("tanh(inf)",               Ok(1.0)),
+    tanh_neginf:            ("tanh(-inf)",              Ok(-1.0)),
 }
@@ -0,0 +1,138 @@
+extern crate rustcalc;
+
+use rustcalc::environment::Environment;
+use rustcalc::error::EvalError;
+use rustcalc::parser::Parser;
+use rustcalc::value::Mode;
+
+macro_rules! test_exact {
+    ( $( $name:ident: ($input:expr, $expect:expr), )* ) => {
+    $(
+        #[test]
+        fn $name() {
+            let mut env = Environment::new();
+            env.set_mode(Mode::Exact);
+            let actual = Parser::new($input).parse().unwrap().evaluate_value(&env);
+            assert_eq!($expect, actual.to_string());
+        }
+    )*
+    }
+}
+
+test_exact! {
+    // Exact integer and rational operations
+    add:                    ("9007199254740992 + 1",        "9007199254740993"),
+    divide_exact:           ("2 ^ 100 / 2 ^ 98",            "4"),
+    divide_fraction:        ("7 / 2",                       "7/2"),
+    divide_thirds:          ("1/3 + 1/3 + 1/3",             "1"),
+    decimal:                ("0.1 + 0.2",                   "3/10"),
+    decimal_exponent:       ("1.5e-3",                      "3/2000"),
+    decimal_integer:        ("1.5 * 2",                     "3"),
+    decimal_negative:       ("-0.25 * 3",                   "-3/4"),
+    exponent:               ("2 ^ 100",                     "1267650600228229401496703205376"),
+    exponent_negative:      ("2 ^ -2",                      "1/4"),
+    exponent_negative_base: ("(-3) ^ 41",                   "-36472996377170786403"),
+    exponent_rational:      ("(2/3) ^ 3",                   "8/27"),
+    exponent_rational_neg:  ("(-2/3) ^ -3",                 "-27/8"),
+    exponent_zero:          ("0 ^ 0",                       "1"),
+    factorial:              ("25!",                         "15511210043330985984000000"),
+    factorial_zero:         ("0!",                          "1"),
+    sum:                    ("sum(1/3, 1/3, 1/3)",          "1"),
+    mean:                   ("mean(1, 2)",                  "3/2"),
+    median:                 ("median(1/2, 1/3, 1/4, 1/5)",  "7/24"),
+    variance:               ("variance(1, 2, 3, 4)",        "5/3"),
+    stdev:                  ("pstdev(2, 4, 4, 4, 5, 5, 7, 9)", "2"),
+    percentile:             ("percentile(10, 0, 1)",        "1/10"),
+    literal:                ("123456789012345678901234567890", "123456789012345678901234567890"),
+    modulo:                 ("(2 ^ 100 + 7) % 10",          "3"),
+    modulo_negative:        ("-7 % 3",                      "2"),
+    modulo_negative_rhs:    ("7 % -3",                      "-2"),
+    modulo_rational:        ("7.5 % 2",                     "3/2"),
+    modulo_rational_neg:    ("-1/3 % 1",                    "2/3"),
+    multiply:               ("99999999999 * 99999999999",   "9999999999800000000001"),
+    negate:                 ("-(2 ^ 64)",                   "-18446744073709551616"),
+    round:                  ("round(2 ^ 70, 2)",            "1180591620717411303424"),
+    round_half:             ("round(1.005, 2)",             "101/100"),
+    round_half_negative:    ("round(-2.5, 0)",              "-3"),
+    round_rational:         ("round(2/3, 3)",               "667/1000"),
+    round_half_even:        ("round(5/2, 0, half_even)",    "2"),
+    roundto:                ("roundto(7/3, 1/4)",           "9/4"),
+    floor:                  ("floor(-7/2)",                 "-4"),
+    ceil:                   ("ceil(2 ^ 70 + 1/3)",          "1180591620717411303425"),
+    frac:                   ("frac(7/2)",                   "1/2"),
+    sign:                   ("sign(-2/3)",                  "-1"),
+    subtract:               ("2 ^ 64 - 1",                  "18446744073709551615"),
+
+    // Fallback to floating point
+    divide_zero:            ("1 / 0",                       "inf"),
+    exponent_fraction:      ("4 ^ 0.5",                     "2"),
+    exponent_huge:          ("2 ^ (2 ^ 64)",                "inf"),
+    exponent_zero_negative: ("0 ^ -1",                      "inf"),
+    factorial_fraction:     ("2.5!",                        "3.3233509704478426"),
+    factorial_negative:     ("(-1)!",                       "NaN"),
+    float_exponent:         ("1e400000 * 0",                "NaN"),
+    function:               ("sqrt(1/4)",                   "0.5"),
+    modulo_zero:            ("5 % 0",                       "NaN"),
+}
+
+#[test]
+fn float_mode() {
+    let env = Environment::new();
+    let value = Parser::new("2 ^ 64 + 1").parse().unwrap().evaluate_value(&env);
+    assert_eq!("18446744073709552000", value.to_string());
+}
+
+#[test]
+fn float_literals() {
+    let env = Environment::new();
+    for literal in &[
+        "0.1",
+        "0.3",
+        "3.14159",
+        "2.2250738585072014e-308",
+        "1.7976931348623157e308",
+        "1e400",
+        "9007199254740993",
+        "123456.789e-5",
+        "18446744073709551617.5",
+    ] {
+        let value = Parser::new(literal).parse().unwrap().evaluate(&env);
+        assert_eq!(literal.parse::<f64>().unwrap(), value, "{}", literal);
+    }
+}
+
+#[test]
+fn mixed_number() {
+    let mut env = Environment::new();
+    env.set_mode(Mode::Exact);
+    let format = |input| format!("{:#}", Parser::new(input).parse().unwrap().evaluate_value(&env));
+    assert_eq!("3 1/2", format("7 / 2"));
+    assert_eq!("-3 1/2", format("-7 / 2"));
+    assert_eq!("2/3", format("2 / 3"));
+    assert_eq!("4", format("8 / 2"));
+}
+
+#[test]
+fn statement_variable() {
+    let mut env = Environment::new();
+    env.set_mode(Mode::Exact);
+    for input in &["x = 30!", "y = x / 29!"] {
+        Parser::with_environment(input, &env).parse_statement().unwrap().execute(&mut env);
+    }
+    assert_eq!("265252859812191058636308480000000", env.value("x").unwrap().to_string());
+    assert_eq!("30", env.value("y").unwrap().to_string());
+}
+
+#[test]
+fn strict() {
+    let mut env = Environment::new();
+    env.set_mode(Mode::Exact);
+    let evaluate = |input| Parser::new(input).parse().unwrap().try_evaluate(&env);
+    assert_eq!(Ok(25852016738884978212864.0), evaluate("23!"));
+    assert_eq!(Err(EvalError::Overflow), evaluate("171!"));
+    assert_eq!(Err(EvalError::DivisionByZero), evaluate("1 % 0"));
+    assert_eq!(
+        Err(EvalError::Domain { function: "factorial".into(), value: -1.0 }),
+        evaluate("(-1)!")
+    );
+}
@@ -0,0 +1,122 @@
+extern crate rustcalc;
+
+use rustcalc::environment::Environment;
+use rustcalc::format::{Formatter, Notation};
+use rustcalc::parser::Parser;
+use rustcalc::value::Mode;
+
+macro_rules! test_format {
+    ( $( $name:ident: ($notation:expr, $input:expr, $expect:expr), )* ) => {
+    $(
+        #[test]
+        fn $name() {
+            let formatter = Formatter { notation: $notation, ..Formatter::default() };
+            let value = Parser::new($input).parse().unwrap().evaluate_value(&Environment::new());
+            assert_eq!($expect, formatter.format(&value));
+        }
+    )*
+    }
+}
+
+macro_rules! test_format_exact {
+    ( $( $name:ident: ($notation:expr, $input:expr, $expect:expr), )* ) => {
+    $(
+        #[test]
+        fn $name() {
+            let mut env = Environment::new();
+            env.set_mode(Mode::Exact);
+            let formatter = Formatter { notation: $notation, ..Formatter::default() };
+            let value = Parser::new($input).parse().unwrap().evaluate_value(&env);
+            assert_eq!($expect, formatter.format(&value));
+        }
+    )*
+    }
+}
+
+macro_rules! test_separators {
+    ( $( $name:ident: ($notation:expr, $input:expr, $expect:expr), )* ) => {
+    $(
+        #[test]
+        fn $name() {
+            let formatter = Formatter { notation: $notation, separators: true, ..Formatter::default() };
+            let value = Parser::new($input).parse().unwrap().evaluate_value(&Environment::new());
+            assert_eq!($expect, formatter.format(&value));
+        }
+    )*
+    }
+}
+
+test_format! {
+    // General
+    general:                (Notation::General,         "1234.5",       "1234.5"),
+    general_complex:        (Notation::General,         "3-4i",         "3-4i"),
+
+    // Fixed
+    fixed:                  (Notation::Fixed(2),        "pi",           "3.14"),
+    fixed_padded:           (Notation::Fixed(3),        "1.5",          "1.500"),
+    fixed_round_up:         (Notation::Fixed(0),        "2.5",          "3"),
+    fixed_negative:         (Notation::Fixed(1),        "-2.25",        "-2.3"),
+    fixed_negative_zero:    (Notation::Fixed(2),        "-0.001",       "0.00"),
+    fixed_small:            (Notation::Fixed(4),        "0.00012",      "0.0001"),
+    fixed_complex:          (Notation::Fixed(1),        "1/3 - 2i",     "0.3-2.0i"),
+    fixed_quantity:         (Notation::Fixed(2),        "5 ft to m",    "1.52 m"),
+    fixed_infinity:         (Notation::Fixed(2),        "1/0",          "inf"),
+    fixed_nan:              (Notation::Fixed(2),        "0/0",          "NaN"),
+
+    // Scientific
+    scientific:             (Notation::Scientific,      "12345.678",    "1.2345678e4"),
+    scientific_integer:     (Notation::Scientific,      "1000",         "1e3"),
+    scientific_negative:    (Notation::Scientific,      "-0.00025",     "-2.5e-4"),
+    scientific_zero:        (Notation::Scientific,      "0",            "0e0"),
+
+    // Engineering
+    engineering:            (Notation::Engineering,     "12345",        "12.345k"),
+    engineering_micro:      (Notation::Engineering,     "0.000123",     "123µ"),
+    engineering_milli:      (Notation::Engineering,     "0.05",         "50m"),
+    engineering_unit:       (Notation::Engineering,     "4700 ohm",     "4.7k ohm"),
+    engineering_plain:      (Notation::Engineering,     "1.5",          "1.5"),
+    engineering_large:      (Notation::Engineering,     "1e30",         "1e30"),
+    engineering_padded:     (Notation::Engineering,     "1e7",          "10M"),
+
+    // Significant
+    significant:            (Notation::Significant(3),  "12345",        "12300"),
+    significant_carry:      (Notation::Significant(3),  "9.999",        "10.0"),
+    significant_small:      (Notation::Significant(3),  "0.00012345",   "0.000123"),
+    significant_noise:      (Notation::Significant(15), "0.1 + 0.2",    "0.300000000000000"),
+    significant_large:      (Notation::Significant(2),  "6.02e23",      "6.0e23"),
+    significant_tiny:       (Notation::Significant(2),  "1.6e-19",      "1.6e-19"),
+}
+
+test_format_exact! {
+    exact_fixed:            (Notation::Fixed(3),        "2/3",          "0.667"),
+    exact_fixed_large:      (Notation::Fixed(1),        "2^70",         "1180591620717411303424.0"),
+    exact_scientific:       (Notation::Scientific,      "1/3",          "3.3333333333333333e-1"),
+    exact_scientific_int:   (Notation::Scientific,      "2^70",         "1.180591620717411303424e21"),
+    exact_significant:      (Notation::Significant(4),  "1/7",          "0.1429"),
+    exact_engineering:      (Notation::Engineering,     "1/8",          "125m"),
+}
+
+test_separators! {
+    separators:             (Notation::General,         "1234567.891",  "1,234,567.891"),
+    separators_short:       (Notation::General,         "123",          "123"),
+    separators_negative:    (Notation::Fixed(2),        "-1234567",     "-1,234,567.00"),
+    separators_fraction:    (Notation::General,         "0.0000123",    "0.0000123"),
+    separators_exponent:    (Notation::Scientific,      "12345678",     "1.2345678e7"),
+    separators_complex:     (Notation::General,         "1000+2000i",   "1,000+2,000i"),
+}
+
+#[test]
+fn base() {
+    let formatter = Formatter { base: 16, separators: true, ..Formatter::default() };
+    let value = Parser::new("65535").parse().unwrap().evaluate_value(&Environment::new());
+    assert_eq!("0xFFFF", formatter.format(&value));
+}
+
+#[test]
+fn mixed() {
+    let mut env = Environment::new();
+    env.set_mode(Mode::Exact);
+    let formatter = Formatter { mixed: true, ..Formatter::default() };
+    let value = Parser::new("3.5 m").parse().unwrap().evaluate_value(&env);
+    assert_eq!("3 1/2 m", formatter.format(&value));
+}
@@ -0,0 +1,142 @@
+extern crate rustcalc;
+
+use rustcalc::environment::Environment;
+use rustcalc::format::Formatter;
+use rustcalc::parser::Parser;
+use rustcalc::value::Mode;
+
+macro_rules! test_integer {
+    ( $( $name:ident: ($input:expr, $expect:expr), )* ) => {
+    $(
+        #[test]
+        fn $name() {
+            let mut env = Environment::new();
+            env.set_mode(Mode::Exact);
+            let actual = Parser::new($input).parse().unwrap().evaluate_value(&env);
+            assert_eq!($expect, actual.to_string());
+        }
+    )*
+    }
+}
+
+macro_rules! test_integer_float {
+    ( $( $name:ident: ($input:expr, $expect:expr), )* ) => {
+    $(
+        #[test]
+        fn $name() {
+            let env = Environment::new();
+            let actual = Parser::new($input).parse().unwrap().evaluate_value(&env);
+            assert_eq!($expect, actual.to_string());
+        }
+    )*
+    }
+}
+
+macro_rules! test_factors {
+    ( $( $name:ident: ($input:expr, $expect:expr), )* ) => {
+    $(
+        #[test]
+        fn $name() {
+            let mut env = Environment::new();
+            env.set_mode(Mode::Exact);
+            let statement = Parser::new($input).parse_statement().unwrap();
+            let formatter = Formatter { factors: statement.factors(), ..Formatter::default() };
+            let value = statement.execute(&mut env).unwrap();
+            assert_eq!($expect, formatter.format(&value));
+        }
+    )*
+    }
+}
+
+test_integer! {
+    // Combinatorics
+    binomial:               ("choose(5, 2)",                "10"),
+    binomial_ncr:           ("nCr(52, 5)",                  "2598960"),
+    binomial_large:         ("nCr(100, 50)",                "100891344545564193334812497256"),
+    binomial_zero:          ("nCr(5, 0)",                   "1"),
+    binomial_too_many:      ("nCr(3, 5)",                   "0"),
+    binomial_huge:          ("nCr(10^9, 5*10^8)",           "inf"),
+    binomial_huge_n:        ("nCr(10^300, 100000)",         "inf"),
+    permutations:           ("nPr(5, 2)",                   "20"),
+    permutations_all:       ("nPr(25, 25)",                 "15511210043330985984000000"),
+    permutations_too_many:  ("nPr(3, 5)",                   "0"),
+    permutations_huge_n:    ("nPr(10^300, 100000)",         "inf"),
+    double_factorial:       ("25!!",                        "7905853580625"),
+    double_factorial_even:  ("30!!",                        "42849873690624000"),
+    double_factorial_huge:  ("(10^9)!!",                    "inf"),
+
+    // Divisibility
+    gcd:                    ("gcd(12, 18)",                 "6"),
+    gcd_negative:           ("gcd(-12, 18)",                "6"),
+    gcd_zero:               ("gcd(0, 5)",                   "5"),
+    gcd_large:              ("gcd(2^100, 6^50)",            "1125899906842624"),
+    lcm:                    ("lcm(4, 6)",                   "12"),
+    lcm_large:              ("lcm(2^64 + 1, 2^64 - 1)",     "340282366920938463463374607431768211455"),
+    lcm_zero:               ("lcm(0, 5)",                   "0"),
+
+    // Primes
+    isprime:                ("isprime(97)",                 "1"),
+    isprime_composite:      ("isprime(91)",                 "0"),
+    isprime_one:            ("isprime(1)",                  "0"),
+    isprime_negative:       ("isprime(-7)",                 "0"),
+    isprime_mersenne:       ("isprime(2^61 - 1)",           "1"),
+    isprime_carmichael:     ("isprime(3215031751)",         "0"),
+    isprime_large:          ("isprime(2^89 - 1)",           "1"),
+    nextprime:              ("nextprime(13)",               "17"),
+    nextprime_negative:     ("nextprime(-5)",               "2"),
+    nextprime_large:        ("nextprime(2^64)",             "18446744073709551629"),
+    totient:                ("totient(36)",                 "12"),
+    totient_one:            ("totient(1)",                  "1"),
+    totient_prime:          ("totient(2^61 - 1)",           "2305843009213693950"),
+
+    // Modular arithmetic
+    modpow:                 ("modpow(4, 13, 497)",          "445"),
+    modpow_large:           ("modpow(3, 10^18, 10^9 + 7)",  "246336683"),
+    modpow_negative_base:   ("modpow(-2, 3, 5)",            "2"),
+    modpow_inverse:         ("modpow(3, -1, 7)",            "5"),
+    modpow_zero:            ("modpow(5, 0, 1)",             "0"),
+    modinv:                 ("modinv(3, 11)",               "4"),
+    modinv_negative:        ("modinv(-3, 11)",              "7"),
+    modinv_large:           ("modinv(2, 2^61 - 1)",         "1152921504606846976"),
+    modinv_not_coprime:     ("modinv(4, 8)",                "NaN"),
+
+    // Non-integers
+    gcd_fraction:           ("gcd(1/2, 3)",                 "NaN"),
+    isprime_fraction:       ("isprime(7/2)",                "NaN"),
+    binomial_negative:      ("nCr(-1, 2)",                  "NaN"),
+    totient_zero:           ("totient(0)",                  "NaN"),
+
+    // Too large to compute
+    isprime_huge:           ("isprime(2^1279 - 1)",         "NaN"),
+    nextprime_huge:         ("nextprime(2^1024)",           "NaN"),
+    nextprime_limit:        ("nextprime(2^1024 - 1)",       "NaN"),
+    factor_hard:            ("factor((2^61 - 1) * (2^89 - 1))", "NaN"),
+    factor_huge:            ("factor(2^300)",               "NaN"),
+    totient_hard:           ("totient((2^61 - 1) * (2^89 - 1))", "NaN"),
+}
+
+test_integer_float! {
+    float_binomial:         ("nCr(100, 50)",                "100891344545564193334812497256"),
+    float_factorial_double: ("30!!",                        "42849873690624000"),
+    float_isprime:          ("isprime(2^53 - 111)",         "1"),
+    float_isprime_composite: ("isprime(2^53 - 1)",          "0"),
+    float_nextprime:        ("nextprime(2^52)",             "4503599627370517"),
+    float_gcd:              ("gcd(2^52, 6^20)",             "1048576"),
+    float_isprime_rounded:  ("isprime(2305843009213693951)", "NaN"),
+    float_factor_rounded:   ("factor(2^127 - 1)",           "NaN"),
+    float_factor_product:   ("factor(1000000007 * 998244353 * 1000000009)", "NaN"),
+    float_gcd_rounded:      ("gcd(2^60, 6)",                "NaN"),
+    float_arithmetic:       ("nCr(100, 50) + 1",            "100891344545564200000000000000"),
+}
+
+test_factors! {
+    factor:                 ("factor(360)",                 "2^3 * 3^2 * 5"),
+    factor_prime:           ("factor(97)",                  "97"),
+    factor_negative:        ("factor(-12)",                 "-2^2 * 3"),
+    factor_one:             ("factor(1)",                   "1"),
+    factor_zero:            ("factor(0)",                   "0"),
+    factor_large:           ("factor(2^64 + 1)",            "274177 * 67280421310721"),
+    factor_semiprime:       ("factor(1000003 * 1000033)",   "1000003 * 1000033"),
+    factor_assign:          ("n = factor(1001)",            "7 * 11 * 13"),
+    factor_nested:          ("factor(12) + 1",              "13"),
+}
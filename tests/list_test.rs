@@ -0,0 +1,139 @@
+extern crate rustcalc;
+
+use rustcalc::environment::Environment;
+use rustcalc::error::{Error, EvalError, Expected};
+use rustcalc::lexer::{Span, Token};
+use rustcalc::parser::Parser;
+use rustcalc::value::{Mode, Value};
+
+macro_rules! test_list {
+    ( $( $name:ident: ($input:expr, $expect:expr), )* ) => {
+    $(
+        #[test]
+        fn $name() {
+            let mut env = Environment::new();
+            env.set("v", Value::List(vec![Value::from(1), Value::from(2), Value::from(3)]));
+            let actual = Parser::with_environment($input, &env).parse().unwrap().evaluate_value(&env);
+            assert_eq!($expect, actual.to_string());
+        }
+    )*
+    }
+}
+
+macro_rules! test_list_exact {
+    ( $( $name:ident: ($input:expr, $expect:expr), )* ) => {
+    $(
+        #[test]
+        fn $name() {
+            let mut env = Environment::new();
+            env.set_mode(Mode::Exact);
+            let actual = Parser::new($input).parse().unwrap().evaluate_value(&env);
+            assert_eq!($expect, actual.to_string());
+        }
+    )*
+    }
+}
+
+macro_rules! test_list_error {
+    ( $( $name:ident: ($input:expr, $expect:expr), )* ) => {
+    $(
+        #[test]
+        fn $name() {
+            let env = Environment::new();
+            let actual = Parser::new($input).parse().unwrap().try_evaluate(&env);
+            assert_eq!(Err($expect), actual);
+        }
+    )*
+    }
+}
+
+macro_rules! test_list_parse {
+    ( $( $name:ident: ($input:expr, $expect:expr), )* ) => {
+    $(
+        #[test]
+        fn $name() {
+            let actual = Parser::new($input).parse();
+            assert_eq!($expect, actual.unwrap_err());
+        }
+    )*
+    }
+}
+
+test_list! {
+    // Literals
+    literal:                ("[1, 2, 3]",               "[1, 2, 3]"),
+    literal_empty:          ("[]",                      "[]"),
+    literal_single:         ("[4]",                     "[4]"),
+    literal_expressions:    ("[1 + 1, 2 * 3]",          "[2, 6]"),
+    literal_nested:         ("[[1, 2], [3]]",           "[[1, 2], [3]]"),
+    variable:               ("v",                       "[1, 2, 3]"),
+
+    // Element-wise operations
+    add:                    ("[1, 2] + [10, 20]",       "[11, 22]"),
+    add_scalar:             ("[1, 2] + 1",              "[2, 3]"),
+    subtract_scalar:        ("10 - [1, 2]",             "[9, 8]"),
+    multiply_scalar:        ("[1, 2, 3] * 2",           "[2, 4, 6]"),
+    multiply:               ("v * v",                   "[1, 4, 9]"),
+    divide:                 ("[1, 2] / 4",              "[0.25, 0.5]"),
+    modulo:                 ("[5, 6, 7] % 3",           "[2, 0, 1]"),
+    exponent:               ("2 ^ v",                   "[2, 4, 8]"),
+    negate:                 ("-v",                      "[-1, -2, -3]"),
+    nested:                 ("[[1, 2], [3, 4]] * 10",   "[[10, 20], [30, 40]]"),
+    function:               ("sqrt([1, 4, 9])",         "[1, 2, 3]"),
+    function_trig:          ("sin([0, pi / 2])",        "[0, 1]"),
+    function_round:         ("round([1.44, 2.66], 1)",  "[1.4, 2.7]"),
+    quantities:             ("[1 m, 2 m] + 50 cm",      "[1.5 m, 2.5 m]"),
+    equal:                  ("[1, 2] == [1, 2]",        "1"),
+    equal_length:           ("[1, 2] == [1, 2, 3]",     "0"),
+
+    // Indexing
+    index:                  ("v[0]",                    "1"),
+    index_last:             ("v[2]",                    "3"),
+    index_negative:         ("v[-1]",                   "3"),
+    index_literal:          ("[4, 5, 6][1]",            "5"),
+    index_nested:           ("[[1, 2], [3, 4]][1][0]",  "3"),
+    index_expression:       ("v[1 + 1] * 2",            "6"),
+    index_negate:           ("-v[0]",                   "-1"),
+
+    // Functions
+    len:                    ("len(v)",                  "3"),
+    len_empty:              ("len([])",                 "0"),
+    sum:                    ("sum(v)",                  "6"),
+    sum_mixed:              ("sum([1, 2], 3)",          "6"),
+    sum_empty:              ("sum([])",                 "0"),
+    product_empty:          ("product([])",             "1"),
+    mean_empty:             ("mean([])",                "NaN"),
+    max:                    ("max([3, 7, 5])",          "7"),
+    dot:                    ("dot([1, 2, 3], [4, 5, 6])", "32"),
+    dot_empty:              ("dot([], [])",             "0"),
+    cross:                  ("cross([1, 0, 0], [0, 1, 0])", "[0, 0, 1]"),
+    cross_anticommutative:  ("cross([0, 1, 0], [1, 0, 0])", "[0, 0, -1]"),
+    cross_length:           ("cross([1, 2], [3, 4])",   "NaN"),
+    norm:                   ("norm([3, 4])",            "5"),
+    norm_negative:          ("norm([-2, 1, -2])",       "3"),
+    norm_quantities:        ("norm([3 m, 4 m])",        "5 m"),
+}
+
+test_list_exact! {
+    exact_multiply:         ("[1/2, 1/3] * 3",          "[3/2, 1]"),
+    exact_mean:             ("mean([1, 2])",            "3/2"),
+    exact_dot:              ("dot([1/2, 1/3], [2, 3])", "2"),
+}
+
+test_list_error! {
+    length_mismatch:        ("[1, 2] + [1, 2, 3]",      EvalError::LengthMismatch { lhs: 2, rhs: 3 }),
+    length_mismatch_dot:    ("dot([1], [1, 2])",        EvalError::LengthMismatch { lhs: 1, rhs: 2 }),
+    expected_list:          ("len(3)",                  EvalError::ExpectedList),
+    expected_list_index:    ("3[0]",                    EvalError::ExpectedList),
+    index_out_of_range:     ("[1, 2][2]",               EvalError::InvalidIndex { index: 2.0, length: 2 }),
+    index_negative_range:   ("[1, 2][-3]",              EvalError::InvalidIndex { index: -3.0, length: 2 }),
+    index_fraction:         ("[1, 2][0.5]",             EvalError::InvalidIndex { index: 0.5, length: 2 }),
+    divide_by_zero:         ("[1, 2] / 0",              EvalError::DivisionByZero),
+    cross_length_strict:    ("cross([1, 2], [3, 4])",   EvalError::Domain { function: "cross".into(), value: 2.0 }),
+}
+
+test_list_parse! {
+    parse_unclosed:         ("[1, 2",                   Error::UnexpectedEnd { span: Span::new(5, 5) }),
+    parse_separator:        ("[1 2]",                   Error::UnexpectedToken { found: Token::Number("2".into()), expected: Expected::Token(Token::Comma), span: Span::new(3, 4) }),
+    parse_index_unclosed:   ("[1][0",                   Error::UnexpectedEnd { span: Span::new(5, 5) }),
+}
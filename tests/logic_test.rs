@@ -0,0 +1,141 @@
+extern crate rustcalc;
+
+use rustcalc::environment::Environment;
+use rustcalc::error::{Error, EvalError, Expected};
+use rustcalc::lexer::{Span, Token};
+use rustcalc::parser::Parser;
+use rustcalc::value::Mode;
+
+macro_rules! test_logic {
+    ( $( $name:ident: ($input:expr, $expect:expr), )* ) => {
+    $(
+        #[test]
+        fn $name() {
+            let mut env = Environment::new();
+            env.set("x", 150.0);
+            let actual = Parser::with_environment($input, &env).parse().unwrap().evaluate_value(&env);
+            assert_eq!($expect, actual.to_string());
+        }
+    )*
+    }
+}
+
+macro_rules! test_logic_strict {
+    ( $( $name:ident: ($input:expr, $expect:expr), )* ) => {
+    $(
+        #[test]
+        fn $name() {
+            let env = Environment::new();
+            let expect: Result<f64, EvalError> = $expect;
+            assert_eq!(expect, Parser::new($input).parse().unwrap().try_evaluate(&env));
+        }
+    )*
+    }
+}
+
+test_logic! {
+    // Comparisons
+    less:                   ("1 < 2",                   "1"),
+    less_false:             ("2 < 1",                   "0"),
+    less_equal:             ("2 <= 2",                  "1"),
+    greater:                ("3 > 2",                   "1"),
+    greater_equal:          ("2 >= 3",                  "0"),
+    equal:                  ("1 == 1.0",                "1"),
+    equal_float:            ("0.1 + 0.2 == 0.3",        "0"),
+    not_equal:              ("3 != 6",                  "1"),
+    not_equal_factorial:    ("3! == 6",                 "1"),
+    nan_equal:              ("nan == nan",              "0"),
+    nan_not_equal:          ("nan != nan",              "1"),
+    nan_less:               ("nan < 1",                 "0"),
+    infinity:               ("-inf < inf",              "1"),
+    complex_equal:          ("2i == 2i",                "1"),
+    complex_less:           ("2i < 3",                  "0"),
+    quantity:               ("3 km > 200 m",            "1"),
+    quantity_equal:         ("1 km == 1000 m",          "1"),
+
+    // Chained comparisons
+    chained:                ("1 < 2 < 3",               "1"),
+    chained_descending:     ("3 > 2 > 1",               "1"),
+    chained_mixed:          ("1 < 3 >= 3 != 4",         "1"),
+    chained_parens:         ("(3 > 2) > 1",             "0"),
+
+    // Boolean operators
+    and:                    ("1 and 2",                 "1"),
+    and_false:              ("1 and 0",                 "0"),
+    or:                     ("0 or 3",                  "1"),
+    or_false:               ("0 or 0",                  "0"),
+    not:                    ("not 0",                   "1"),
+    not_nonzero:            ("not 5",                   "0"),
+    not_not:                ("not not 5",               "1"),
+    not_nan:                ("not nan",                 "NaN"),
+    and_nan:                ("1 and nan",               "NaN"),
+    and_nan_false:          ("0 and nan",               "0"),
+    or_nan:                 ("nan or 0",                "NaN"),
+    or_nan_true:            ("1 or nan",                "1"),
+
+    // Conditionals
+    if_then:                ("if(x > 100, x * 0.9, x)", "135"),
+    if_otherwise:           ("if(x > 200, x * 0.9, x)", "150"),
+    if_nested:              ("if(x < 100, 1, if(x < 200, 2, 3))", "2"),
+    if_lazy:                ("if(1, 2, sqrt(-1) < 1)",  "2"),
+    if_nan:                 ("if(0 / 0, 1, 2)",         "NaN"),
+
+    // Precedence
+    precedence_add:         ("1 + 2 == 3",              "1"),
+    precedence_bitwise:     ("2 < 3 | 4",               "1"),
+    precedence_and_or:      ("1 or 0 and 0",            "1"),
+    precedence_not:         ("not 1 == 2",              "1"),
+    precedence_not_and:     ("not 0 and 0",             "0"),
+}
+
+test_logic_strict! {
+    short_circuit_and:      ("0 and 1 / 0",             Ok(0.0)),
+    short_circuit_or:       ("1 or 1 / 0",              Ok(1.0)),
+    short_circuit_if:       ("if(0, 1 / 0, 2)",         Ok(2.0)),
+    complex_ordering:       ("(1 + i) < 2",             Err(EvalError::NotReal)),
+    undefined_condition:    ("if(inf - inf, 1, 2)",     Err(EvalError::Undefined)),
+    undefined_not:          ("not (inf - inf)",         Err(EvalError::Undefined)),
+    list_condition:         ("if([1], 2, 3)",           Err(EvalError::UnexpectedList)),
+    list_not:               ("not [0]",                 Err(EvalError::UnexpectedList)),
+    list_and:               ("1 and [0]",               Err(EvalError::UnexpectedList)),
+    incompatible_units:     ("3 m < 2 s",               Err(EvalError::IncompatibleUnits {
+                                                            lhs: "m".into(),
+                                                            rhs: "s".into(),
+                                                        })),
+}
+
+#[test]
+fn exact_equal() {
+    let mut env = Environment::new();
+    env.set_mode(Mode::Exact);
+    let actual = Parser::new("0.1 + 0.2 == 0.3").parse().unwrap().evaluate_value(&env);
+    assert_eq!("1", actual.to_string());
+}
+
+#[test]
+fn definition() {
+    let mut env = Environment::new();
+    Parser::new("price(q) = if(q > 100, q * 0.9, q)").parse_statement().unwrap().execute(&mut env);
+    let actual = Parser::with_environment("price(50) + price(200)", &env).parse().unwrap();
+    assert_eq!(230.0, actual.evaluate(&env));
+}
+
+#[test]
+fn not_operand_missing() {
+    let actual = Parser::new("1 + not 0").parse().unwrap_err();
+    let expect = Error::UnknownConstant { name: "not".into(), span: Span::new(4, 7) };
+    assert_eq!(expect, actual);
+}
+
+#[test]
+fn not_equal_lexing() {
+    let actual = Parser::new("3!=6").parse().unwrap().evaluate(&Environment::new());
+    assert_eq!(1.0, actual);
+    let actual = Parser::new("3 !== 6").parse().unwrap_err();
+    let expect = Error::UnexpectedToken {
+        found: Token::Equals,
+        expected: Expected::Value,
+        span: Span::new(4, 5),
+    };
+    assert_eq!(expect, actual);
+}
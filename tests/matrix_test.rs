@@ -0,0 +1,157 @@
+extern crate rustcalc;
+
+use rustcalc::environment::Environment;
+use rustcalc::error::EvalError;
+use rustcalc::parser::Parser;
+use rustcalc::value::{Mode, Value};
+
+macro_rules! test_matrix {
+    ( $( $name:ident: ($input:expr, $expect:expr), )* ) => {
+    $(
+        #[test]
+        fn $name() {
+            let mut env = Environment::new();
+            let rows = vec![vec![Value::from(1), Value::from(2)], vec![Value::from(3), Value::from(4)]];
+            env.set("a", Value::List(rows.into_iter().map(Value::List).collect()));
+            let actual = Parser::with_environment($input, &env).parse().unwrap().evaluate_value(&env);
+            assert_eq!($expect, actual.to_string());
+        }
+    )*
+    }
+}
+
+macro_rules! test_matrix_exact {
+    ( $( $name:ident: ($input:expr, $expect:expr), )* ) => {
+    $(
+        #[test]
+        fn $name() {
+            let mut env = Environment::new();
+            env.set_mode(Mode::Exact);
+            let actual = Parser::new($input).parse().unwrap().evaluate_value(&env);
+            assert_eq!($expect, actual.to_string());
+        }
+    )*
+    }
+}
+
+macro_rules! test_matrix_error {
+    ( $( $name:ident: ($input:expr, $expect:expr), )* ) => {
+    $(
+        #[test]
+        fn $name() {
+            let env = Environment::new();
+            let actual = Parser::new($input).parse().unwrap().try_evaluate(&env);
+            assert_eq!(Err($expect), actual);
+        }
+    )*
+    }
+}
+
+test_matrix! {
+    // Multiplication
+    multiply:               ("a * [[5, 6], [7, 8]]",            "[[19, 22], [43, 50]]"),
+    multiply_rectangular:   ("[[1, 2, 3]] * [[1], [2], [3]]",   "[[14]]"),
+    multiply_outer:         ("[[1], [2]] * [[3, 4]]",           "[[3, 4], [6, 8]]"),
+    multiply_vector:        ("a * [1, 1]",                      "[3, 7]"),
+    multiply_row_vector:    ("[1, 1] * a",                      "[4, 6]"),
+    multiply_chained:       ("a * a * [1, 0]",                  "[7, 15]"),
+    multiply_scalar:        ("a * 2",                           "[[2, 4], [6, 8]]"),
+    multiply_scalar_lhs:    ("2 * a",                           "[[2, 4], [6, 8]]"),
+    multiply_elements:      ("[1, 2] * [3, 4]",                 "[3, 8]"),
+    add:                    ("a + identity(2)",                 "[[2, 2], [3, 5]]"),
+    index:                  ("a[1][0]",                         "3"),
+
+    // Powers
+    power:                  ("a ^ 2",                           "[[7, 10], [15, 22]]"),
+    power_one:              ("a ^ 1",                           "[[1, 2], [3, 4]]"),
+    power_zero:             ("a ^ 0",                           "[[1, 0], [0, 1]]"),
+    power_negative:         ("[[2, 0], [0, 4]] ^ -2",           "[[0.25, 0], [0, 0.0625]]"),
+    power_singular:         ("[[1, 2], [2, 4]] ^ -1",           "NaN"),
+    power_fraction:         ("a ^ 0.5",                         "NaN"),
+    power_huge:             ("[[1, 1], [1, 0]] ^ 10000",        "[[inf, inf], [inf, inf]]"),
+    power_vector:           ("[1, 2] ^ 2",                      "[1, 4]"),
+
+    // Functions
+    transpose:              ("transpose([[1, 2, 3], [4, 5, 6]])", "[[1, 4], [2, 5], [3, 6]]"),
+    transpose_vector:       ("transpose([1, 2])",               "[[1], [2]]"),
+    transpose_column:       ("transpose([[1], [2]])",           "[[1, 2]]"),
+    transpose_units:        ("transpose([[1 m, 2 m]])",         "[[1 m], [2 m]]"),
+    det:                    ("det(a)",                          "-2"),
+    det_single:             ("det([[5]])",                      "5"),
+    det_swap:               ("det([[0, 1], [1, 0]])",           "-1"),
+    det_diagonal:           ("det([[2, 0, 0], [0, 3, 0], [0, 0, 4]])", "24"),
+    det_singular:           ("det([[1, 2], [2, 4]])",           "0"),
+    det_identity:           ("det(identity(3) * 2)",            "8"),
+    inv:                    ("inv([[2, 0], [0, 4]])",           "[[0.5, 0], [0, 0.25]]"),
+    inv_permutation:        ("inv([[0, 1], [1, 0]])",           "[[0, 1], [1, 0]]"),
+    inv_singular:           ("inv([[1, 2], [2, 4]])",           "NaN"),
+    rank:                   ("rank(a)",                         "2"),
+    rank_singular:          ("rank([[1, 2, 3], [2, 4, 6]])",    "1"),
+    rank_tall:              ("rank([[1, 2], [3, 4], [5, 6]])",  "2"),
+    rank_zero:              ("rank(zeros(3))",                  "0"),
+    solve:                  ("solve([[2, 1], [1, 3]], [3, 5])", "[0.8, 1.4]"),
+    solve_matrix:           ("solve([[0, 1], [1, 0]], a)",      "[[3, 4], [1, 2]]"),
+    solve_singular:         ("solve([[1, 2], [2, 4]], [1, 1])", "NaN"),
+    eig:                    ("eig([[2, 1], [1, 2]])",           "[1, 3]"),
+    eig_diagonal:           ("eig([[3, 0], [0, -1]])",          "[-1, 3]"),
+    eig_swap:               ("eig([[0, 1], [1, 0]])",           "[-1, 1]"),
+    eig_trace:              ("sum(eig([[4, 1, 2], [1, 3, 0], [2, 0, 5]]))", "12"),
+    eig_det:                ("round(product(eig([[4, 1, 2], [1, 3, 0], [2, 0, 5]])), 9)", "43"),
+    eig_complex:            ("eig([[1, i], [i, 1]])",           "NaN"),
+
+    // Constructors
+    identity:               ("identity(3)",                     "[[1, 0, 0], [0, 1, 0], [0, 0, 1]]"),
+    identity_multiply:      ("identity(2) * a",                 "[[1, 2], [3, 4]]"),
+    zeros:                  ("zeros(2, 3)",                     "[[0, 0, 0], [0, 0, 0]]"),
+    zeros_square:           ("zeros(2)",                        "[[0, 0], [0, 0]]"),
+    identity_fraction:      ("identity(2.5)",                   "NaN"),
+    zeros_zero:             ("zeros(0)",                        "NaN"),
+    zeros_huge:             ("zeros(2000)",                     "NaN"),
+}
+
+test_matrix_exact! {
+    exact_det:              ("det([[1, 2], [3, 4]])",           "-2"),
+    exact_det_fractions:    ("det([[1/2, 1/3], [1/4, 1/5]])",   "1/60"),
+    exact_inv:              ("inv([[1, 2], [3, 4]])",           "[[-2, 1], [3/2, -1/2]]"),
+    exact_inv_product:      ("inv([[2, 1], [7, 4]]) * [[2, 1], [7, 4]]", "[[1, 0], [0, 1]]"),
+    exact_solve:            ("solve([[1, 2], [3, 4]], [5, 6])", "[-4, 9/2]"),
+    exact_eig:              ("eig([[2, 1], [1, 2]])",           "[1, 3]"),
+    exact_power:            ("[[1, 1], [1, 0]] ^ 10",           "[[89, 55], [55, 34]]"),
+    exact_power_negative:   ("[[1, 2], [3, 4]] ^ -1",           "[[-2, 1], [3/2, -1/2]]"),
+}
+
+test_matrix_error! {
+    expected_matrix:        ("det([1, 2])",                     EvalError::ExpectedMatrix),
+    expected_matrix_scalar: ("inv(3)",                          EvalError::ExpectedMatrix),
+    expected_matrix_ragged: ("rank([[1, 2], [3]])",             EvalError::ExpectedMatrix),
+    expected_matrix_empty:  ("transpose([])",                   EvalError::ExpectedMatrix),
+    not_square:             ("det([[1, 2, 3]])",                EvalError::NotSquare { rows: 1, columns: 3 }),
+    not_square_inv:         ("inv([[1], [2]])",                 EvalError::NotSquare { rows: 2, columns: 1 }),
+    not_symmetric:          ("eig([[1, 2], [3, 4]])",           EvalError::NotSymmetric),
+    singular:               ("inv([[1, 2], [2, 4]])",           EvalError::Singular),
+    singular_solve:         ("solve(zeros(2), [1, 1])",         EvalError::Singular),
+    mismatch_multiply:      ("[[1, 2, 3]] * [[1, 2], [3, 4]]",  EvalError::LengthMismatch { lhs: 3, rhs: 2 }),
+    mismatch_vector:        ("[[1, 2]] * [1, 2, 3]",            EvalError::LengthMismatch { lhs: 2, rhs: 3 }),
+    mismatch_solve:         ("solve([[1, 0], [0, 1]], [1, 2, 3])", EvalError::LengthMismatch { lhs: 2, rhs: 3 }),
+    complex_eig_strict:     ("eig([[1, i], [i, 1]])",           EvalError::NotReal),
+    unit_multiply:          ("[[1 m]] * [[2]]",                 EvalError::UnexpectedUnit { unit: "m".into() }),
+    unit_det:               ("det([[1 m, 0], [0, 1]])",         EvalError::UnexpectedUnit { unit: "m".into() }),
+    not_square_power:       ("[[1, 2, 3]] ^ 2",                 EvalError::NotSquare { rows: 1, columns: 3 }),
+    singular_power:         ("[[1, 2], [2, 4]] ^ -1",           EvalError::Singular),
+    fraction_power:         ("[[1, 2], [3, 4]] ^ 0.5",          EvalError::Domain { function: "^".into(), value: 0.5 }),
+    unit_power:             ("[[1 m]] ^ 2",                     EvalError::UnexpectedUnit { unit: "m".into() }),
+    zeros_negative:         ("zeros(-1)",                       EvalError::Domain { function: "zeros".into(), value: -1.0 }),
+    identity_huge:          ("identity(2000)",                  EvalError::Domain { function: "identity".into(), value: 2000.0 }),
+}
+
+#[test]
+fn power_products() {
+    let mut env = Environment::new();
+    env.set("a", Parser::new("[[1, 2], [3, 4]]").parse().unwrap().evaluate_value(&env));
+    let evaluate =
+        |input| Parser::with_environment(input, &env).parse().unwrap().evaluate_value(&env);
+    assert_eq!(evaluate("a * a"), evaluate("a ^ 2"));
+    assert_eq!(evaluate("a * a * a"), evaluate("a ^ 3"));
+    assert_eq!(evaluate("inv(a)"), evaluate("a ^ -1"));
+    assert_eq!(evaluate("inv(a) * inv(a)"), evaluate("a ^ -2"));
+}
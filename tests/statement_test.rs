@@ -0,0 +1,97 @@
+extern crate rustcalc;
+
+use std::f64;
+
+use rustcalc::environment::Environment;
+use rustcalc::error::{Error, Expected};
+use rustcalc::function::Arity;
+use rustcalc::lexer::{Span, Token};
+use rustcalc::parser::Parser;
+
+macro_rules! test_execute {
+    ( $( $name:ident: ([$( $input:expr ),*], $expect:expr), )* ) => {
+    $(
+        #[test]
+        fn $name() {
+            let expect: Result<f64, Error> = $expect;
+            let mut env = Environment::new();
+            let mut actual = Ok(None);
+            for input in &[$( $input ),*] {
+                actual = Parser::with_environment(input, &env)
+                    .parse_statement()
+                    .map(|statement| statement.execute(&mut env).map(f64::from));
+                if actual.is_err() {
+                    break;
+                }
+            }
+            match expect {
+                Ok(v) if v.is_nan() => assert!(actual.unwrap().unwrap().is_nan(), "Expected NaN"),
+                _ => assert_eq!(expect.map(Some), actual),
+            }
+        }
+    )*
+    }
+}
+
+test_execute! {
+    expression:             (["1 + 2"],                                           Ok(3.0)),
+
+    // Assignment
+    assign:                 (["x = 3"],                                           Ok(3.0)),
+    assign_expression:      (["x = 3 * pi"],                                      Ok(3.0 * f64::consts::PI)),
+    assign_nospace:         (["x=3"],                                             Ok(3.0)),
+    assign_lookup:          (["x = 3", "x"],                                      Ok(3.0)),
+    assign_lookup_expr:     (["x = 3", "2 * x + 1"],                              Ok(7.0)),
+    assign_reassign:        (["x = 3", "x = 4", "x"],                             Ok(4.0)),
+    assign_self:            (["x = 3", "x = x + 1", "x"],                         Ok(4.0)),
+    assign_chain:           (["x = 2", "y = x ^ 3", "y"],                         Ok(8.0)),
+    assign_multiple:        (["a = 2", "b = 3", "a * b"],                         Ok(6.0)),
+    assign_long_name:       (["a_LoNg_1 = 1", "a_LoNg_1"],                        Ok(1.0)),
+    assign_utf8:            (["銹 = 1", "銹 + 1"],                                Ok(2.0)),
+    assign_nan:             (["x = nan", "x"],                                    Ok(f64::NAN)),
+    assign_case_sensitive:  (["x = 1", "X"],                                      Err(Error::UnknownConstant { name: "X".into(), span: Span::new(0, 1) })),
+    assign_unknown:         (["x = y"],                                           Err(Error::UnknownConstant { name: "y".into(), span: Span::new(4, 5) })),
+    assign_unbound_self:    (["x = x + 1"],                                       Err(Error::UnknownConstant { name: "x".into(), span: Span::new(4, 5) })),
+    assign_failed:          (["x = y", "x"],                                      Err(Error::UnknownConstant { name: "y".into(), span: Span::new(4, 5) })),
+    assign_constant:        (["pi = 3"],                                          Err(Error::AssignConstant { name: "pi".into(), span: Span::new(0, 2) })),
+    assign_constant_case:   (["E = 3"],                                           Err(Error::AssignConstant { name: "E".into(), span: Span::new(0, 1) })),
+    assign_empty:           (["x ="],                                             Err(Error::UnexpectedEnd { span: Span::new(3, 3) })),
+    assign_number:          (["1 = 2"],                                           Err(Error::UnexpectedToken { found: Token::Equals, expected: Expected::End, span: Span::new(2, 3) })),
+    assign_expression_lhs:  (["x = 1", "x + 1 = 2"],                              Err(Error::UnexpectedToken { found: Token::Equals, expected: Expected::End, span: Span::new(6, 7) })),
+    assign_nested:          (["x = y = 1"],                                       Err(Error::UnknownConstant { name: "y".into(), span: Span::new(4, 5) })),
+    assign_in_expression:   (["1 + (x = 2)"],                                     Err(Error::UnknownConstant { name: "x".into(), span: Span::new(5, 6) })),
+    assign_function:        (["f(x) = x", "f = 2", "f(f)"],                       Ok(2.0)),
+
+    // Function definitions
+    define:                 (["f(x) = x ^ 2", "f(3)"],                            Ok(9.0)),
+    define_multi:           (["f(x, y) = x ^ 2 + y", "f(2, 3)"],                  Ok(7.0)),
+    define_noargs:          (["f() = 2 * pi", "f()"],                             Ok(2.0 * f64::consts::PI)),
+    define_nospace:         (["f(x,y)=x-y", "f(1,2)"],                            Ok(-1.0)),
+    define_param_order:     (["f(x, y) = x / y", "f(1, 2)"],                      Ok(0.5)),
+    define_param_unused:    (["f(x) = 1", "f(2)"],                                Ok(1.0)),
+    define_param_expr:      (["f(x) = x + 1", "f(2 * 3)"],                        Ok(7.0)),
+    define_param_shadow:    (["x = 1", "f(x) = x", "f(2)"],                       Ok(2.0)),
+    define_param_scope:     (["f(x) = x", "f(2)", "x"],                           Err(Error::UnknownConstant { name: "x".into(), span: Span::new(0, 1) })),
+    define_variable:        (["a = 2", "f(x) = a * x", "f(3)"],                   Ok(6.0)),
+    define_variable_late:   (["a = 2", "f(x) = a * x", "a = 3", "f(3)"],          Ok(9.0)),
+    define_variable_unknown:(["f(x) = a * x"],                                    Err(Error::UnknownConstant { name: "a".into(), span: Span::new(7, 8) })),
+    define_nested:          (["f(x) = x + 1", "g(x) = 2 * f(x)", "g(3)"],         Ok(8.0)),
+    define_nested_arg:      (["f(x) = x + 1", "f(f(1))"],                         Ok(3.0)),
+    define_redefine:        (["f(x) = x", "f(x) = 2 * x", "f(3)"],                Ok(6.0)),
+    define_redefine_early:  (["f(x) = x", "g(x) = f(x)", "f(x) = 2 * x", "g(3)"], Ok(3.0)),
+    define_redefine_self:   (["f(x) = x", "f(x) = f(x) + 1", "f(3)"],             Ok(4.0)),
+    define_recursive:       (["f(x) = f(x)"],                                     Err(Error::UnknownFunction { name: "f".into(), span: Span::new(7, 8) })),
+    define_case_sensitive:  (["f(x) = x", "F(1)"],                                Err(Error::UnknownFunction { name: "F".into(), span: Span::new(0, 1) })),
+    define_builtin:         (["sqrt(x) = x"],                                     Err(Error::RedefineFunction { name: "sqrt".into(), span: Span::new(0, 4) })),
+    define_builtin_case:    (["Sin(x) = x"],                                      Err(Error::RedefineFunction { name: "Sin".into(), span: Span::new(0, 3) })),
+    define_param_constant:  (["f(pi) = pi"],                                      Err(Error::InvalidParameter { name: "pi".into(), span: Span::new(2, 4) })),
+    define_param_duplicate: (["f(x, x) = x"],                                     Err(Error::DuplicateParameter { name: "x".into(), span: Span::new(5, 6) })),
+    define_param_number:    (["f(1) = 1"],                                        Err(Error::UnknownFunction { name: "f".into(), span: Span::new(0, 1) })),
+    define_param_missing:   (["f(x, y) = x", "f(1)"],                             Err(Error::ArityMismatch { name: "f".into(), expected: Arity::exact(2), got: 1, span: Span::new(0, 4) })),
+    define_param_extra:     (["f(x) = x", "f(1, 2)"],                             Err(Error::ArityMismatch { name: "f".into(), expected: Arity::exact(1), got: 2, span: Span::new(0, 7) })),
+    define_noargs_extra:    (["f() = 1", "f(1)"],                                 Err(Error::ArityMismatch { name: "f".into(), expected: Arity::exact(0), got: 1, span: Span::new(0, 4) })),
+    define_empty:           (["f(x) ="],                                          Err(Error::UnexpectedEnd { span: Span::new(6, 6) })),
+    define_trail_comma:     (["f(x,) = x"],                                       Err(Error::UnknownConstant { name: "x".into(), span: Span::new(2, 3) })),
+    define_no_parens:       (["f(x) = x", "f 1"],                                 Err(Error::UnknownConstant { name: "f".into(), span: Span::new(0, 1) })),
+    define_undefined:       (["f(1)"],                                            Err(Error::UnknownFunction { name: "f".into(), span: Span::new(0, 1) })),
+}
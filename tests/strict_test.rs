@@ -0,0 +1,148 @@
+extern crate rustcalc;
+
+use std::f64;
+
+use rustcalc::environment::Environment;
+use rustcalc::error::{Error, EvalError};
+use rustcalc::parser::Parser;
+
+macro_rules! test_strict {
+    ( $( $name:ident: ($input:expr, $expect:expr), )* ) => {
+    $(
+        #[test]
+        fn $name() {
+            let mut env = Environment::new();
+            env.set("x", 2.0);
+            env.register_function("fail", 0, |_| f64::NAN);
+            let expect: Result<f64, EvalError> = $expect;
+            let actual = Parser::with_environment($input, &env).parse().unwrap().try_evaluate(&env);
+            assert_eq!(expect, actual);
+        }
+    )*
+    }
+}
+
+test_strict! {
+    // Valid results
+    number:                 ("1 + 2 * 3",       Ok(7.0)),
+    number_inf:             ("inf",             Ok(f64::INFINITY)),
+    number_inf_arithmetic:  ("inf * 2 - 1",     Ok(f64::INFINITY)),
+    exponent_negative:      ("2 ^ -1",          Ok(0.5)),
+    exponent_negative_base: ("(-8) ^ 2",        Ok(64.0)),
+    factorial:              ("5!",              Ok(120.0)),
+    round:                  ("round(3.14, 1)",  Ok(3.1)),
+    sqrt:                   ("sqrt(4)",         Ok(2.0)),
+    variable:               ("x * 3",           Ok(6.0)),
+
+    // Division by zero
+    divide_zero:            ("1 / 0",           Err(EvalError::DivisionByZero)),
+    divide_zero_zero:       ("0 / 0",           Err(EvalError::DivisionByZero)),
+    divide_zero_negative:   ("1 / -0",          Err(EvalError::DivisionByZero)),
+    exponent_zero_negative: ("0 ^ -1",          Err(EvalError::DivisionByZero)),
+    modulo_zero:            ("5 % 0",           Err(EvalError::DivisionByZero)),
+    nested_zero:            ("1 / (x - 2)",     Err(EvalError::DivisionByZero)),
+
+    // Domain errors and complex results
+    exponent_fraction:      ("(-8) ^ (1 / 3)",  Err(EvalError::NotReal)),
+    factorial_fraction:     ("2.5!",            Ok(3.3233509704478426)),
+    factorial_negative:     ("(-1)!",           Err(EvalError::Domain { function: "factorial".into(), value: -1.0 })),
+    gamma_pole:             ("gamma(-1)",       Err(EvalError::Domain { function: "gamma".into(), value: -1.0 })),
+    lgamma_pole:            ("lgamma(0)",       Err(EvalError::Domain { function: "lgamma".into(), value: 0.0 })),
+    ln_pole:                ("ln(0)",           Err(EvalError::Domain { function: "ln".into(), value: 0.0 })),
+    log_pole:               ("log(0, 10)",      Err(EvalError::Domain { function: "log".into(), value: 0.0 })),
+    log2_pole:              ("log2(0)",         Err(EvalError::Domain { function: "log2".into(), value: 0.0 })),
+    log1p_pole:             ("log1p(-1)",       Err(EvalError::Domain { function: "log1p".into(), value: -1.0 })),
+    atanh_pole:             ("atanh(1)",        Err(EvalError::Domain { function: "atanh".into(), value: 1.0 })),
+    tan_pole:               ("tan(pi / 2)",     Err(EvalError::Domain { function: "tan".into(), value: f64::consts::FRAC_PI_2 })),
+    cot_pole:               ("cot(0)",          Err(EvalError::Domain { function: "cot".into(), value: 0.0 })),
+    min_complex:            ("min(1, i)",       Err(EvalError::NotReal)),
+    variance_one:           ("variance(2)",     Err(EvalError::Domain { function: "variance".into(), value: 2.0 })),
+    percentile_range:       ("percentile(-1, 2)", Err(EvalError::Domain { function: "percentile".into(), value: -1.0 })),
+    gcd_fraction:           ("gcd(2.5, 5)",     Err(EvalError::Domain { function: "gcd".into(), value: 2.5 })),
+    modinv_not_coprime:     ("modinv(4, 8)",    Err(EvalError::Domain { function: "modinv".into(), value: 4.0 })),
+    modpow_zero_modulus:    ("modpow(2, 3, 0)", Err(EvalError::Domain { function: "modpow".into(), value: 0.0 })),
+    isprime_rounded:        ("isprime(2^61 - 1)", Err(EvalError::Domain { function: "isprime".into(), value: 2305843009213693952.0 })),
+    binomial_negative:      ("nCr(5, -1)",      Err(EvalError::Domain { function: "nCr".into(), value: -1.0 })),
+    double_factorial_neg:   ("(-3)!!",          Err(EvalError::Domain { function: "!!".into(), value: -3.0 })),
+    round_fraction:         ("round(1, 0.5)",   Err(EvalError::Domain { function: "round".into(), value: 0.5 })),
+    round_negative:         ("round(1, -1)",    Err(EvalError::Domain { function: "round".into(), value: -1.0 })),
+    roundto_zero:           ("roundto(1, 0)",   Err(EvalError::Domain { function: "roundto".into(), value: 0.0 })),
+    sqrt_negative:          ("sqrt(-4)",        Err(EvalError::NotReal)),
+    sqrt_negative_utf8:     ("√-x",             Err(EvalError::NotReal)),
+
+    // Overflow
+    overflow_exponent:      ("10 ^ 400",        Err(EvalError::Overflow)),
+    overflow_factorial:     ("171!",            Err(EvalError::Overflow)),
+    overflow_literal:       ("1e400",           Ok(f64::INFINITY)),
+    overflow_multiply:      ("1e200 * 1e200",   Err(EvalError::Overflow)),
+    overflow_exp:           ("exp(1000)",       Err(EvalError::Overflow)),
+
+    // Undefined results
+    undefined_inf:          ("inf - inf",       Err(EvalError::Undefined)),
+    undefined_nan:          ("nan",             Err(EvalError::Undefined)),
+    undefined_native:       ("fail()",          Err(EvalError::Undefined)),
+    undefined_sine:         ("sin(inf)",        Err(EvalError::Undefined)),
+}
+
+macro_rules! test_try_execute {
+    ( $( $name:ident: ([$( $input:expr ),*], $expect:expr), )* ) => {
+    $(
+        #[test]
+        fn $name() {
+            let mut env = Environment::new();
+            let expect: Result<Option<f64>, Error> = $expect;
+            let mut actual = Ok(None);
+            for input in &[$( $input ),*] {
+                actual = Parser::with_environment(input, &env)
+                    .parse_statement()
+                    .and_then(|stmt| Ok(stmt.try_execute(&mut env)?.map(f64::from)));
+            }
+            assert_eq!(expect, actual);
+        }
+    )*
+    }
+}
+
+test_try_execute! {
+    execute_assign:         (["x = 1 / 4"],                 Ok(Some(0.25))),
+    execute_assign_error:   (["x = 1", "x = 1 / 0", "x"],   Ok(Some(1.0))),
+    execute_define:         (["f(a) = 1 / a"],              Ok(None)),
+    execute_define_error:   (["f(a) = 1 / a", "f(0)"],      Err(EvalError::DivisionByZero.into())),
+    execute_error:          (["(-1)!"],                     Err(EvalError::Domain { function: "factorial".into(), value: -1.0 }.into())),
+}
+
+#[test]
+fn unknown_variable() {
+    let mut env = Environment::new();
+    env.set("x", 1.0);
+    let expr = Parser::with_environment("x + 1", &env).parse().unwrap();
+    assert_eq!(
+        expr.try_evaluate(&Environment::new()),
+        Err(EvalError::UnknownVariable { name: "x".into() })
+    );
+    assert!(expr.evaluate(&Environment::new()).is_nan());
+}
+
+#[test]
+fn messages() {
+    assert_eq!(Error::from(EvalError::DivisionByZero).to_string(), "Division by zero");
+    assert_eq!(
+        EvalError::Domain { function: "sqrt".into(), value: -4.0 }.to_string(),
+        "Invalid argument -4 for sqrt"
+    );
+    assert_eq!(EvalError::NotReal.to_string(), "Result is not a real number");
+    assert_eq!(EvalError::Overflow.to_string(), "Numeric overflow");
+    assert_eq!(EvalError::Undefined.to_string(), "Undefined result");
+    assert_eq!(EvalError::UnknownVariable { name: "x".into() }.to_string(), "Unknown variable x");
+    assert_eq!(Error::from(EvalError::Overflow).span(), None);
+}
+
+#[test]
+fn lenient() {
+    let env = Environment::new();
+    let evaluate = |input| Parser::new(input).parse().unwrap().evaluate(&env);
+    assert_eq!(evaluate("1 / 0"), f64::INFINITY);
+    assert_eq!(evaluate("10 ^ 400"), f64::INFINITY);
+    assert!(evaluate("sqrt(-4)").is_nan());
+    assert!(evaluate("(-1)!").is_nan());
+}
@@ -0,0 +1,170 @@
+extern crate rustcalc;
+
+use rustcalc::environment::Environment;
+use rustcalc::error::{Error, EvalError, Expected};
+use rustcalc::lexer::{Span, Token};
+use rustcalc::parser::Parser;
+use rustcalc::value::Mode;
+
+macro_rules! test_unit {
+    ( $( $name:ident: ($input:expr, $expect:expr), )* ) => {
+    $(
+        #[test]
+        fn $name() {
+            let mut env = Environment::new();
+            env.set("t", 2.0);
+            let actual = Parser::with_environment($input, &env).parse().unwrap().evaluate_value(&env);
+            assert_eq!($expect, actual.to_string());
+        }
+    )*
+    }
+}
+
+macro_rules! test_unit_exact {
+    ( $( $name:ident: ($input:expr, $expect:expr), )* ) => {
+    $(
+        #[test]
+        fn $name() {
+            let mut env = Environment::new();
+            env.set_mode(Mode::Exact);
+            let actual = Parser::new($input).parse().unwrap().evaluate_value(&env);
+            assert_eq!($expect, actual.to_string());
+        }
+    )*
+    }
+}
+
+macro_rules! test_unit_error {
+    ( $( $name:ident: ($input:expr, $expect:expr), )* ) => {
+    $(
+        #[test]
+        fn $name() {
+            let env = Environment::new();
+            let actual = Parser::new($input).parse().unwrap().try_evaluate(&env);
+            assert_eq!(Err($expect), actual);
+        }
+    )*
+    }
+}
+
+macro_rules! test_unit_parse {
+    ( $( $name:ident: ($input:expr, $expect:expr), )* ) => {
+    $(
+        #[test]
+        fn $name() {
+            let actual = Parser::new($input).parse();
+            assert_eq!($expect, actual.unwrap_err());
+        }
+    )*
+    }
+}
+
+fn incompatible(lhs: &str, rhs: &str) -> EvalError {
+    EvalError::IncompatibleUnits { lhs: lhs.into(), rhs: rhs.into() }
+}
+
+fn non_integer(quantity: &str) -> EvalError {
+    EvalError::NonIntegerPower { quantity: quantity.into() }
+}
+
+fn unexpected(unit: &str) -> EvalError {
+    EvalError::UnexpectedUnit { unit: unit.into() }
+}
+
+test_unit! {
+    // Literals
+    literal:                ("3 m",                     "3 m"),
+    literal_adjacent:       ("3m",                      "3 m"),
+    literal_compound:       ("9.81 m/s^2",              "9.81 m/s^2"),
+    literal_juxtaposed:     ("2 N m",                   "2 N*m"),
+    literal_negative_power: ("2 m^-1",                  "2 m^-1"),
+    literal_prefix:         ("5 km",                    "5 km"),
+    literal_prefix_micro:   ("5 µs",                    "5 µs"),
+    literal_product:        ("5 kg*m/s^2",              "5 kg*m/s^2"),
+    literal_shadowed:       ("3 m / t",                 "1.5 m"),
+
+    // Arithmetic
+    add:                    ("3 km + 200 m",            "3.2 km"),
+    add_same:               ("1 m + 2 m",               "3 m"),
+    divide_dimensionless:   ("3 km / 2 m",              "1500"),
+    divide_per:             ("1 / (4 s)",               "0.25 s^-1"),
+    divide_scalar:          ("6 m / 2",                 "3 m"),
+    divide_velocity:        ("100 km / 2 h",            "50 km/h"),
+    modulo:                 ("7 m % 2 m",               "1 m"),
+    multiply_derived:       ("9.81 m/s^2 * 70 kg",      "686.7 N"),
+    multiply_kept:          ("2 N * 3 m",               "6 N*m"),
+    multiply_merged:        ("2 km * 500 m",            "1 km^2"),
+    multiply_rate:          ("100 km/h * 2 h",          "200 km"),
+    multiply_scalar:        ("2 * 3 m",                 "6 m"),
+    negate:                 ("-3 m",                    "-3 m"),
+    power:                  ("(2 m)^3",                 "8 m^3"),
+    power_negative:         ("(2 s)^-1",                "0.5 s^-1"),
+    power_fractional:       ("(4 m^2)^0.5",             "2 m"),
+    power_fractional_units: ("(9 m^2/s^4)^-0.5",        "0.3333333333333333 s^2/m"),
+    subtract:               ("1 h - 30 min",            "0.5 h"),
+
+    // Conversions
+    convert:                ("5 ft to m",               "1.524 m"),
+    convert_compound:       ("60 mi/h to km/h",         "96.56064 km/h"),
+    convert_derived:        ("1 kW * 2 h to J",         "7200000 J"),
+    convert_expression:     ("3 km + 200 m to m",       "3200 m"),
+    convert_in:             ("1 inch in cm",            "2.54 cm"),
+    convert_nested:         ("(1 h to min) * 2",        "120 min"),
+    convert_pressure:       ("1 atm to kPa",            "101.325 kPa"),
+    convert_twice:          ("1 km to m to cm",         "100000 cm"),
+
+    // Functions
+    abs:                    ("abs(-3 m)",               "3 m"),
+    round:                  ("round(3.14159 m, 2)",     "3.14 m"),
+    roundto:                ("roundto(1234 m, 100)",    "1200 m"),
+    roundto_unit:           ("roundto(1234 m, 1 km)",   "1000 m"),
+    sum:                    ("sum(1 m, 50 cm)",         "1.5 m"),
+    max:                    ("max(1 km, 500 m)",        "1 km"),
+    mean:                   ("mean(1 m, 2 m, 3 m)",     "2 m"),
+    pstdev:                 ("pstdev(1 s, 3 s)",        "1 s"),
+    pvariance:              ("pvariance(1 s, 3 s)",     "1 s^2"),
+    percentile:             ("percentile(50, 1 m, 2 m)", "1.5 m"),
+    floor:                  ("floor(2.5 kg)",           "2 kg"),
+    frac:                   ("frac(5.75 h)",            "0.75 h"),
+    sign:                   ("sign(-2 m)",              "-1"),
+    sqrt:                   ("sqrt(16 m^2)",            "4 m"),
+
+    // Invalid use in non-strict mode
+    lenient_add:            ("3 m + 2 s",               "NaN"),
+}
+
+test_unit_exact! {
+    exact_add:              ("3 km + 200 m",            "16/5 km"),
+    exact_convert:          ("5 ft to m",               "381/250 m"),
+    exact_derived:          ("9.81 m/s^2 * 70 kg",      "6867/10 N"),
+    exact_power_fraction:   ("(16 m^4)^(3/4)",          "8 m^3"),
+}
+
+test_unit_error! {
+    add_incompatible:       ("3 m + 2 s",               incompatible("m", "s")),
+    add_dimensionless:      ("3 m + 2",                 incompatible("m", "1")),
+    convert_incompatible:   ("5 ft to s",               incompatible("ft", "s")),
+    convert_dimensionless:  ("5 to m",                  incompatible("1", "m")),
+    exponent_fractional:    ("(4 m)^0.5",               non_integer("4 m")),
+    exponent_fraction:      ("(4 m^2)^(1/3)",           non_integer("4 m^2")),
+    exponent_unit:          ("2^(3 m)",                 unexpected("m")),
+    factorial:              ("(3 m)!",                  unexpected("m")),
+    modulo_incompatible:    ("3 m % 2 s",               incompatible("m", "s")),
+    sine:                   ("sin(3 m)",                unexpected("m")),
+    sqrt_odd:               ("sqrt(2 m)",               unexpected("m")),
+    subtract_incompatible:  ("1 N - 1 J",               incompatible("N", "J")),
+}
+
+test_unit_parse! {
+    convert_missing:        ("5 m to",      Error::UnexpectedEnd { span: Span::new(6, 6) }),
+    convert_unknown:        ("5 m to x",    Error::UnexpectedToken {
+                                                found: Token::Ident("x".into()),
+                                                expected: Expected::Unit,
+                                                span: Span::new(7, 8),
+                                            }),
+    unknown_unit:           ("5 parsec",    Error::UnexpectedToken {
+                                                found: Token::Ident("parsec".into()),
+                                                expected: Expected::End,
+                                                span: Span::new(2, 8),
+                                            }),
+}